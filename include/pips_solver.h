@@ -0,0 +1,43 @@
+/* C API of the pips_solver shared library (libpips_solver.so / .dylib / pips_solver.dll). */
+#ifndef PIPS_SOLVER_H
+#define PIPS_SOLVER_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Status codes. The first three match the command line tool's exit codes for the same outcomes; PIPS_USAGE has no
+   command line counterpart. */
+#define PIPS_OK 0
+#define PIPS_ERROR 1       /* the puzzle could not be loaded, or an internal error (panic) */
+#define PIPS_UNSOLVABLE 2  /* the puzzle has no solution */
+#define PIPS_USAGE 3       /* null pointer, non-UTF-8 string or unknown solver name */
+
+/* A loaded puzzle and its current (possibly empty) solution. */
+typedef struct PipsPuzzle PipsPuzzle;
+
+/* Load a puzzle from a JSON document (the format read by the command line tool). */
+int pips_puzzle_from_json(const char *json, PipsPuzzle **out);
+
+/* Solve with the named backend ("backtrack", "dlx", or NULL for the default), keeping the solution. */
+int pips_solve(PipsPuzzle *puzzle, const char *solver);
+
+/* Count solutions, stopping after `limit` (0 for no limit). Clears any kept solution. */
+int pips_count_solutions(PipsPuzzle *puzzle, const char *solver, uint64_t limit, uint64_t *count);
+
+/* The puzzle and its current solution as a JSON document, or NULL on failure. Free with pips_string_free. */
+char *pips_result_json(const PipsPuzzle *puzzle);
+
+/* Message of the last failure on this thread, valid until the next call into the library. */
+const char *pips_last_error(void);
+
+void pips_puzzle_free(PipsPuzzle *puzzle);
+void pips_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,88 @@
+//! Frames for animated output (`--animate`): board states recorded from the search trace.
+//!
+//! Each frame is the list of dominoes on the board at that moment, in the puzzle's coordinates; render a frame by
+//! loading it with `GameGrid::apply_placements` and using any renderer (see `svg::render_animation` for SVG).
+
+use std::sync::{Arc, Mutex};
+
+use crate::grid::{GameGrid, Placement};
+use crate::solver::{SolveOptions, Solver};
+use crate::trace::TraceEvent;
+
+/// Longest search animation; later steps are dropped, but the solution is always the last frame.
+pub const MAX_SEARCH_FRAMES: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimateMode {
+    /// The solution placed one domino per frame, in the order the search placed them.
+    Solution,
+    /// Every placement and backtrack of the search.
+    Search,
+}
+
+impl AnimateMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "solution" => Some(AnimateMode::Solution),
+            "search" => Some(AnimateMode::Search),
+            _ => None,
+        }
+    }
+}
+
+/// Solve `g` with `solver` and return the frames, starting from the empty board; `None` if there is no solution.
+/// Leaves the solution on the board.
+pub fn frames(g: &mut GameGrid, solver: &dyn Solver, mode: AnimateMode) -> Option<Vec<Vec<Placement>>> {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    g.set_trace(Some(Box::new(move |e| {
+        if matches!(e, TraceEvent::Place { .. } | TraceEvent::Backtrack { .. }) { sink.lock().unwrap().push(e.clone()); }
+    })));
+    let found = solver.solve(g, &SolveOptions::default()).0;
+    g.set_trace(None);
+    found?;
+    let events = std::mem::take(&mut *events.lock().unwrap());
+
+    let mut board: Vec<Placement> = Vec::new();
+    let mut frames = vec![Vec::new()];
+    for event in events {
+        match event {
+            TraceEvent::Place { cells, pips, .. } => board.push(Placement { cells, pips }),
+            TraceEvent::Backtrack { cells } => board.retain(|p| p.cells != cells),
+            _ => {}
+        }
+        if mode == AnimateMode::Search && frames.len() < MAX_SEARCH_FRAMES { frames.push(board.clone()); }
+    }
+    match mode {
+        // What is left on the board is the solution, in placement order.
+        AnimateMode::Solution => frames.extend((1..=board.len()).map(|n| board[..n].to_vec())),
+        AnimateMode::Search => if frames.last() != Some(&board) { frames.push(board) },
+    }
+    Some(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::GridFile;
+    use crate::solver::Backtracking;
+
+    #[test]
+    fn solution_and_search_frames() {
+        let file: GridFile = serde_json::from_str(
+            r#"{"grid": [{"rule": "<5", "coords": [[1,1],[2,1]]}, {"rule": "=", "coords": [[1,2],[2,2]]}], "dominoes": [[3,4],[1,2],[2,2]]}"#).unwrap();
+        let mut g = GameGrid::from_parsed(file.clone());
+        let solution = frames(&mut g, &Backtracking, AnimateMode::Solution).unwrap();
+        assert_eq!(solution.iter().map(Vec::len).collect::<Vec<_>>(), vec![0, 1, 2]);
+        let mut last = solution[2].clone();
+        last.sort_unstable();
+        assert_eq!(last, g.placements());
+
+        let search = frames(&mut GameGrid::from_parsed(file), &Backtracking, AnimateMode::Search).unwrap();
+        assert!(search.len() > solution.len(), "the search also shows dead ends");
+        assert_eq!(search.last(), solution.last());
+
+        let unsolvable: GridFile = serde_json::from_str(r#"{"grid": [{"rule": "=", "coords": [[1,1],[2,1]]}], "dominoes": [[1,2]]}"#).unwrap();
+        assert!(frames(&mut GameGrid::from_parsed(unsolvable), &Backtracking, AnimateMode::Solution).is_none());
+    }
+}
@@ -0,0 +1,249 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use pips_solver::grid::{GameGrid, GridFile};
+use pips_solver::solver::{SolveOptions, Solver};
+use pips_solver::text_format;
+
+/// Output layout for the batch summary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchFormat {
+    Table,
+    Jsonl,
+}
+
+/// Outcome of a single puzzle in a batch run.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchStatus {
+    Solved,
+    Unsolvable,
+    Error(String),
+}
+
+impl BatchStatus {
+    fn label(&self) -> &'static str {
+        match self {
+            BatchStatus::Solved => "solved",
+            BatchStatus::Unsolvable => "unsolvable",
+            BatchStatus::Error(_) => "error",
+        }
+    }
+}
+
+/// Per-puzzle result row: path, status, wall-clock solve time and search nodes visited.
+#[derive(Debug, Clone)]
+pub struct BatchResult {
+    pub path: String,
+    pub status: BatchStatus,
+    pub elapsed: Duration,
+    pub nodes: u64,
+}
+
+/// Expand the command-line inputs into a list of puzzle files.
+/// Directories contribute their `*.json` and `*.pips` files (never `*.txt`), and a `*` / `?` wildcard in the final path
+/// component is matched against the entries of its parent directory. Everything else is taken as a plain file path. Expanded entries are sorted by name.
+pub fn collect_paths(inputs: &[String]) -> Result<Vec<PathBuf>, String> {
+    let mut paths = Vec::new();
+    for input in inputs {
+        let p = Path::new(input);
+        if p.is_dir() {
+            let mut found = list_matching(p, "*.json")?;
+            found.extend(list_matching(p, &format!("*.{}", text_format::EXTENSION))?);
+            found.sort();
+            paths.extend(found);
+        } else if has_wildcard(input) {
+            let dir = match p.parent() {
+                Some(d) if !d.as_os_str().is_empty() => d,
+                _ => Path::new("."),
+            };
+            if has_wildcard(&dir.to_string_lossy()) {
+                return Err(format!("Wildcards are only supported in the file name: {input}"));
+            }
+            let pattern = p.file_name().map(|f| f.to_string_lossy().into_owned()).unwrap_or_default();
+            let matched = list_matching(dir, &pattern)?;
+            if matched.is_empty() {
+                return Err(format!("No files match {input}"));
+            }
+            paths.extend(matched);
+        } else {
+            paths.push(p.to_path_buf());
+        }
+    }
+    Ok(paths)
+}
+
+fn has_wildcard(s: &str) -> bool { s.contains('*') || s.contains('?') }
+
+fn list_matching(dir: &Path, pattern: &str) -> Result<Vec<PathBuf>, String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+    let mut out: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .filter(|p| p.file_name().is_some_and(|n| wildcard_match(pattern, &n.to_string_lossy())))
+        .collect();
+    out.sort();
+    Ok(out)
+}
+
+/// Minimal glob matcher supporting `*` (any run of characters) and `?` (exactly one character).
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let n: Vec<char> = name.chars().collect();
+    let (mut pi, mut ni) = (0usize, 0usize);
+    let mut star: Option<(usize, usize)> = None; // (pattern index after '*', name index it matched up to)
+    while ni < n.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == n[ni]) {
+            pi += 1; ni += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi + 1, ni));
+            pi += 1;
+        } else if let Some((sp, sn)) = star {
+            pi = sp; ni = sn + 1;
+            star = Some((sp, sn + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+/// Load and solve a single puzzle, timing the search.
+pub fn solve_one(path: &Path, solver: &dyn Solver) -> BatchResult {
+    let name = path.to_string_lossy().into_owned();
+    let mut g = match GridFile::from_path(&name) {
+        Ok(file) => {
+            crate::warn_overlaps(&file, Some(&name));
+            GameGrid::from_parsed(file)
+        }
+        Err(e) => return BatchResult { path: name, status: BatchStatus::Error(e.to_string()), elapsed: Duration::ZERO, nodes: 0 },
+    };
+    let start = Instant::now();
+    let (found, stats) = solver.solve(&mut g, &SolveOptions::default());
+    let status = if found.is_some() { BatchStatus::Solved } else { BatchStatus::Unsolvable };
+    BatchResult { path: name, status, elapsed: start.elapsed(), nodes: stats.nodes }
+}
+
+/// Solve every path using up to `jobs` worker threads. Results keep the order of `paths`.
+pub fn solve_all(paths: &[PathBuf], jobs: usize, solver: &dyn Solver) -> Vec<BatchResult> {
+    let jobs = jobs.clamp(1, paths.len().max(1));
+    if jobs == 1 {
+        return paths.iter().map(|p| solve_one(p, solver)).collect();
+    }
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<BatchResult>>> = Mutex::new(vec![None; paths.len()]);
+    std::thread::scope(|s| {
+        for _ in 0..jobs {
+            s.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                if i >= paths.len() { break; }
+                let r = solve_one(&paths[i], solver);
+                results.lock().unwrap()[i] = Some(r);
+            });
+        }
+    });
+    results.into_inner().unwrap().into_iter().map(|r| r.expect("every path is solved")).collect()
+}
+
+/// Render results as an aligned plain-text table followed by a one-line total.
+pub fn render_table(results: &[BatchResult]) -> String {
+    use std::fmt::Write;
+    let width = results.iter().map(|r| r.path.len()).max().unwrap_or(0).max("puzzle".len());
+    let mut out = String::new();
+    writeln!(out, "{:<width$}  {:<10}  {:>10}  {:>10}", "puzzle", "status", "time_ms", "nodes").ok();
+    for r in results {
+        write!(out, "{:<width$}  {:<10}  {:>10.3}  {:>10}", r.path, r.status.label(), r.elapsed.as_secs_f64() * 1000.0, r.nodes).ok();
+        if let BatchStatus::Error(msg) = &r.status { write!(out, "  {msg}").ok(); }
+        out.push('\n');
+    }
+    let solved = results.iter().filter(|r| r.status == BatchStatus::Solved).count();
+    writeln!(out, "{solved}/{} solved", results.len()).ok();
+    out
+}
+
+/// Render results as JSON Lines, one object per puzzle.
+pub fn render_jsonl(results: &[BatchResult]) -> String {
+    let mut out = String::new();
+    for r in results {
+        let mut obj = serde_json::json!({
+            "path": r.path,
+            "status": r.status.label(),
+            "time_ms": r.elapsed.as_secs_f64() * 1000.0,
+            "nodes": r.nodes,
+        });
+        if let BatchStatus::Error(msg) = &r.status { obj["error"] = msg.clone().into(); }
+        out.push_str(&obj.to_string());
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pips_solver::dlx::Dlx;
+    use pips_solver::solver::Backtracking;
+
+    fn fixture_dir() -> String {
+        let mut p = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        p.push("tests/grids");
+        p.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn wildcard_matching() {
+        assert!(wildcard_match("*.json", "easy_grid.json"));
+        assert!(wildcard_match("*_grid.json", "hard_grid.json"));
+        assert!(wildcard_match("e?sy*", "easy_grid.json"));
+        assert!(!wildcard_match("*.json", "easy_grid.txt"));
+        assert!(!wildcard_match("hard*", "easy_grid.json"));
+    }
+
+    #[test]
+    fn collect_directory_and_glob() {
+        let all = collect_paths(&[fixture_dir()]).unwrap();
+        assert!(all.len() >= 5);
+        assert!(all.windows(2).all(|w| w[0] <= w[1]), "directory listing should be sorted");
+        let glob = collect_paths(&[format!("{}/e*.json", fixture_dir())]).unwrap();
+        assert_eq!(glob.len(), 1);
+        assert!(glob[0].ends_with("easy_grid.json"));
+    }
+
+    #[test]
+    fn directory_scans_skip_txt_files() {
+        let dir = std::env::temp_dir().join(format!("pips_batch_scan_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["a.json", "b.pips", "notes.txt"] { fs::write(dir.join(name), "").unwrap(); }
+        let found = collect_paths(&[dir.to_string_lossy().into_owned()]).unwrap();
+        let named = collect_paths(&[dir.join("notes.txt").to_string_lossy().into_owned()]).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(found, vec![dir.join("a.json"), dir.join("b.pips")]);
+        assert_eq!(named, vec![dir.join("notes.txt")], "a file named explicitly is still taken");
+    }
+
+    #[test]
+    fn parallel_matches_sequential_order() {
+        let paths = collect_paths(&[fixture_dir()]).unwrap();
+        let seq = solve_all(&paths, 1, &Backtracking);
+        let par = solve_all(&paths, 4, &Backtracking);
+        let s: Vec<_> = seq.iter().map(|r| (r.path.clone(), r.status.clone(), r.nodes)).collect();
+        let p: Vec<_> = par.iter().map(|r| (r.path.clone(), r.status.clone(), r.nodes)).collect();
+        assert_eq!(s, p);
+        assert!(seq.iter().any(|r| r.status == BatchStatus::Unsolvable));
+    }
+
+    #[test]
+    fn jsonl_has_one_object_per_line() {
+        let paths = collect_paths(&[fixture_dir()]).unwrap();
+        let results = solve_all(&paths, 2, &Dlx);
+        let out = render_jsonl(&results);
+        assert_eq!(out.lines().count(), results.len());
+        for line in out.lines() {
+            let v: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert!(v["nodes"].as_u64().is_some());
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! Puzzle identity for deduplication (`dedupe`): a canonical form that forgets how a puzzle happens to be written, and
+//! a stable hash of it.
+//!
+//! Two puzzles have the same canonical form when they differ only in region order, coordinate order or convention,
+//! domino order or orientation, `overlap` markers, rule spelling, or a translation of the board. With `symmetric`,
+//! square boards are also identified up to rotation and reflection; other topologies are compared as drawn.
+
+use crate::grid::{Coord, GridEntry, GridFile, NotEqualMode, Rule, DEFAULT_MAX_PIP};
+use crate::json_format;
+use crate::topology::Topology;
+
+/// A map of the plane applied to every cell before translating the board back against the axes.
+type Symmetry = fn(i64, i64) -> (i64, i64);
+
+/// The four rotations of a square board, then their mirror images.
+const SQUARE_SYMMETRIES: [Symmetry; 8] = [
+    |x, y| (x, y), |x, y| (-y, x), |x, y| (-x, -y), |x, y| (y, -x),
+    |x, y| (-x, y), |x, y| (x, -y), |x, y| (y, x), |x, y| (-y, -x),
+];
+
+/// The canonical form of `file`, in the default coordinate convention with the board moved against the axes.
+pub fn canonical_form(file: &GridFile, symmetric: bool) -> GridFile {
+    let file = file.normalised();
+    let symmetries = if symmetric && file.topology.is_square() { &SQUARE_SYMMETRIES[..] } else { &SQUARE_SYMMETRIES[..1] };
+    symmetries.iter()
+        .map(|&f| arrange(&file, f))
+        .min_by_key(json_format::write)
+        .expect("at least the identity")
+}
+
+/// Stable 64-bit FNV-1a hash of the canonical form, as 16 hex digits.
+pub fn puzzle_hash(file: &GridFile, symmetric: bool) -> String {
+    let hash = json_format::write(&canonical_form(file, symmetric)).bytes()
+        .fold(0xcbf2_9ce4_8422_2325u64, |h, b| (h ^ b as u64).wrapping_mul(0x0100_0000_01b3));
+    format!("{hash:016x}")
+}
+
+/// `file` (internal coordinates) mapped through `f`, translated to start at `(1,1)` and with every list sorted.
+fn arrange(file: &GridFile, f: Symmetry) -> GridFile {
+    let mapped = |&(x, y): &Coord| f(x as i64, y as i64);
+    let points: Vec<(i64, i64)> = file.all_cells().map(mapped).collect();
+    let min_x = points.iter().map(|p| p.0).min().unwrap_or(1);
+    let min_y = points.iter().map(|p| p.1).min().unwrap_or(1);
+    // Hex rows alternate, so the board may only move by whole pairs of rows; a torus wraps where it is declared.
+    let (dx, dy) = match file.topology {
+        Topology::Torus { .. } => (0, 0),
+        Topology::Hex => (min_x - 1, (min_y - 1).div_euclid(2) * 2),
+        _ => (min_x - 1, min_y - 1),
+    };
+    let place = |c: &Coord| -> Coord {
+        let (x, y) = mapped(c);
+        ((x - dx) as u32, (y - dy) as u32)
+    };
+    let mut grid: Vec<GridEntry> = file.grid.iter().map(|e| {
+        let mut coords: Vec<Coord> = e.coords.iter().map(place).collect();
+        coords.sort_unstable();
+        coords.dedup();
+        let rule = match Rule::parse(&e.rule, file.not_equal) {
+            Rule::Unknown => e.rule.clone(),
+            rule => rule.token(NotEqualMode::default()),
+        };
+        GridEntry { rule, coords, ..Default::default() }
+    }).collect();
+    grid.sort_by(|a, b| (&a.coords, &a.rule).cmp(&(&b.coords, &b.rule)));
+    let in_region: Vec<Coord> = grid.iter().flat_map(|e| e.coords.iter().copied()).collect();
+    let mut cells: Vec<Coord> = file.cells.iter().map(place).filter(|c| !in_region.contains(c)).collect();
+    cells.sort_unstable();
+    cells.dedup();
+    let mut dominoes: Vec<(u8, u8)> = file.dominoes.iter().map(|&(a, b)| (a.min(b), a.max(b))).collect();
+    dominoes.sort_unstable();
+    let topology = match &file.topology {
+        Topology::Explicit { edges } => {
+            let mut edges: Vec<[Coord; 2]> = edges.iter().map(|[a, b]| {
+                let (a, b) = (place(a), place(b));
+                [a.min(b), a.max(b)]
+            }).collect();
+            edges.sort_unstable();
+            edges.dedup();
+            Topology::Explicit { edges }
+        }
+        other => other.clone(),
+    };
+    GridFile {
+        grid,
+        dominoes,
+        cells,
+        topology,
+        max_pip: file.max_pip.filter(|&m| m != DEFAULT_MAX_PIP),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::test_support::fixture;
+
+    #[test]
+    fn presentation_does_not_change_the_hash() {
+        let original = fixture("medium_grid.json");
+        let mut shuffled = original.clone();
+        shuffled.grid.reverse();
+        for e in &mut shuffled.grid {
+            e.coords.reverse();
+            e.coords.iter_mut().for_each(|c| *c = (c.0 + 3, c.1 + 5));
+        }
+        shuffled.dominoes = shuffled.dominoes.iter().rev().map(|&(a, b)| (b, a)).collect();
+        let hash = puzzle_hash(&original, false);
+        assert_eq!(hash.len(), 16);
+        assert_eq!(puzzle_hash(&shuffled, false), hash);
+        // The same board written top-left origin, 0-based, as [row, col].
+        let top = original.all_cells().map(|c| c.1).max().unwrap();
+        let mut converted = original.clone();
+        converted.coordinates = serde_json::from_str(r#"{"origin": "top-left", "base": 0, "axes": "row-col"}"#).unwrap();
+        for e in &mut converted.grid { e.coords.iter_mut().for_each(|c| *c = (top - c.1, c.0 - 1)); }
+        assert_eq!(puzzle_hash(&converted, false), hash);
+
+        let mut changed = original.clone();
+        changed.dominoes[0].0 ^= 1;
+        assert_ne!(puzzle_hash(&changed, false), hash);
+        assert_eq!(puzzle_hash(&original, false), puzzle_hash(&original, false), "stable across calls");
+    }
+
+    #[test]
+    fn symmetry_is_optional_and_square_only() {
+        let original = fixture("medium_grid.json");
+        let mut mirrored = original.clone();
+        for e in &mut mirrored.grid { e.coords.iter_mut().for_each(|c| *c = (100 - c.0, c.1)); }
+        let mut rotated = original.clone();
+        for e in &mut rotated.grid { e.coords.iter_mut().for_each(|c| *c = (c.1, 100 - c.0)); }
+        assert_ne!(puzzle_hash(&mirrored, false), puzzle_hash(&original, false));
+        assert_eq!(puzzle_hash(&mirrored, true), puzzle_hash(&original, true));
+        assert_eq!(puzzle_hash(&rotated, true), puzzle_hash(&original, true));
+
+        let hex = fixture("hex_grid.json");
+        assert_eq!(canonical_form(&hex, true), canonical_form(&hex, false));
+        let mut shifted = hex.clone();
+        for e in &mut shifted.grid { e.coords.iter_mut().for_each(|c| *c = (c.0 + 1, c.1 + 1)); }
+        assert_ne!(puzzle_hash(&shifted, false), puzzle_hash(&hex, false), "moving a hex board by one row changes its shape");
+    }
+}
@@ -0,0 +1,118 @@
+//! Resumable solution counting (`count --checkpoint`).
+//!
+//! A checkpoint holds the puzzle, the backend and the search frontier (`GameGrid::search_path`), so a long count can
+//! be stopped and picked up later, even in another process:
+//! ```json
+//! {"puzzle":{"grid":[...],"dominoes":[...]},"solver":"dlx","path":[3,0,5],"solutions":1204,"nodes":5531008}
+//! ```
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::grid::{GameGrid, GridFile};
+use crate::solver::{self, SolveOptions, Solver};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub puzzle: GridFile,
+    /// Name of the backend; a path only makes sense to the backend that produced it.
+    pub solver: String,
+    /// Where to continue (see `GameGrid::search_path`); empty at the start.
+    pub path: Vec<usize>,
+    /// Solutions found before `path`.
+    pub solutions: usize,
+    /// Search nodes visited so far, including the few revisited on each resume.
+    pub nodes: u64,
+}
+
+impl Checkpoint {
+    /// A count of `puzzle` that has not started yet.
+    pub fn new(puzzle: GridFile, solver: &dyn Solver) -> Self {
+        Checkpoint { puzzle, solver: solver.name().to_string(), path: Vec::new(), solutions: 0, nodes: 0 }
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+        let checkpoint: Checkpoint = serde_json::from_str(&text).map_err(|e| format!("{path}: {e}"))?;
+        checkpoint.puzzle.validate().map_err(|e| format!("{path}: {e}"))?;
+        Ok(checkpoint)
+    }
+
+    /// Write to `path` through a temporary file, so an interruption never leaves half a checkpoint.
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let tmp = format!("{path}.tmp");
+        let json = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        std::fs::write(&tmp, json + "\n").and_then(|()| std::fs::rename(&tmp, path)).map_err(|e| format!("{path}: {e}"))
+    }
+
+    /// Continue counting in slices of `every`, calling `save` with the updated checkpoint after each slice that does
+    /// not finish. Stops early, after a final `save`, once `stop` is set. Returns whether the count is complete, in
+    /// which case `solutions` is the total.
+    pub fn run(&mut self, every: Duration, stop: &Arc<AtomicBool>, save: &mut dyn FnMut(&Checkpoint) -> Result<(), String>) -> Result<bool, String> {
+        let solver = solver::by_name(&self.solver).ok_or_else(|| format!("Unknown solver '{}' in the checkpoint.", self.solver))?;
+        let mut g = GameGrid::from_parsed(self.puzzle.clone());
+        g.set_stop_flag(Some(stop.clone()));
+        loop {
+            g.resume_at(std::mem::take(&mut self.path));
+            let stats = solver.enumerate(&mut g, &SolveOptions { timeout: Some(every), ..Default::default() }, &mut |_| false);
+            self.solutions += stats.solutions;
+            self.nodes += stats.nodes;
+            if !stats.aborted { return Ok(true); }
+            self.path = g.search_path();
+            save(self)?;
+            if stop.load(Ordering::Relaxed) { return Ok(false); }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::test_support::open_board;
+    use crate::solver::SOLVERS;
+
+    #[test]
+    fn interrupted_counts_add_up() {
+        // 2x3 board with three distinct dominoes and a few rules: a few hundred nodes.
+        let file: GridFile = serde_json::from_str(r#"{"grid": [{"rule": "<9", "coords": [[1,1],[2,1],[3,1]]},
+            {"rule": "x", "coords": [[1,2],[2,2],[3,2]]}], "dominoes": [[1,2],[3,4],[0,5]]}"#).unwrap();
+        for solver in SOLVERS {
+            let total = solver.enumerate(&mut GameGrid::from_parsed(file.clone()), &SolveOptions::default(), &mut |_| false);
+            assert!(total.solutions > 1 && total.nodes > 20, "{}: {total:?}", solver.name());
+            // Stop after every few nodes and resume from the saved path until done.
+            let (mut g, mut solutions, mut path, mut slices) = (GameGrid::from_parsed(file.clone()), 0, Vec::new(), 0);
+            loop {
+                g.resume_at(path);
+                let stats = solver.enumerate(&mut g, &SolveOptions { max_nodes: Some(7), ..Default::default() }, &mut |_| false);
+                solutions += stats.solutions;
+                slices += 1;
+                if !stats.aborted { break; }
+                path = g.search_path();
+            }
+            assert_eq!(solutions, total.solutions, "{}", solver.name());
+            assert!(slices > 3);
+        }
+    }
+
+    #[test]
+    fn stop_flag_saves_and_resumes() {
+        let file = open_board();
+        for solver in SOLVERS {
+            let stop = Arc::new(AtomicBool::new(true));
+            let mut checkpoint = Checkpoint::new(file.clone(), solver);
+            let mut saved = Vec::new();
+            assert_eq!(checkpoint.run(Duration::from_secs(60), &stop, &mut |c| { saved.push(c.clone()); Ok(()) }), Ok(false));
+            assert_eq!(saved, vec![checkpoint.clone()], "saved once, when the flag was read");
+            assert!(checkpoint.nodes == 1024 && !checkpoint.path.is_empty() && checkpoint.solutions > 0, "{}", solver.name());
+
+            let path = std::env::temp_dir().join(format!("pips_checkpoint_{}_{}.json", solver.name(), std::process::id()));
+            let path = path.to_string_lossy().into_owned();
+            checkpoint.save(&path).unwrap();
+            assert_eq!(Checkpoint::load(&path).as_ref(), Ok(&checkpoint));
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+}
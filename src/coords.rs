@@ -0,0 +1,167 @@
+//! Coordinate conventions accepted in puzzle files and their mapping onto the internal convention.
+//!
+//! Internally every cell uses `(x, y)` with the bottom-left corner at `(1,1)` and `y` growing upward. Files may declare
+//! another convention in a `coordinates` object, e.g. `{"origin": "top-left", "base": 0, "axes": "row-col"}`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::grid::Coord;
+
+/// Corner the vertical axis counts from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Origin {
+    #[default]
+    BottomLeft,
+    TopLeft,
+}
+
+/// Order of the two numbers in a coordinate pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Axes {
+    /// `[x, y]`: column first.
+    #[default]
+    Xy,
+    /// `[row, col]`: row first.
+    RowCol,
+}
+
+/// Declared coordinate convention of a puzzle file. The default is the internal convention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CoordSystem {
+    pub origin: Origin,
+    /// Index of the first row / column: 0 or 1.
+    pub base: u32,
+    pub axes: Axes,
+}
+
+impl Default for CoordSystem {
+    fn default() -> Self { CoordSystem { origin: Origin::BottomLeft, base: 1, axes: Axes::Xy } }
+}
+
+impl CoordSystem {
+    pub fn is_default(&self) -> bool { *self == CoordSystem::default() }
+
+    /// Split a user coordinate into (horizontal, vertical) components.
+    fn split(&self, c: Coord) -> (u32, u32) {
+        match self.axes {
+            Axes::Xy => (c.0, c.1),
+            Axes::RowCol => (c.1, c.0),
+        }
+    }
+
+    fn join(&self, horizontal: u32, vertical: u32) -> Coord {
+        match self.axes {
+            Axes::Xy => (horizontal, vertical),
+            Axes::RowCol => (vertical, horizontal),
+        }
+    }
+
+    /// Check the declaration itself and that every coordinate is expressible in it.
+    /// Undeclared (default) coordinates are passed through untouched, as older files may contain zeros.
+    pub fn validate<'a>(&self, coords: impl IntoIterator<Item = &'a Coord>) -> Result<(), String> {
+        if self.base > 1 {
+            return Err(format!("Coordinate base must be 0 or 1, got {}.", self.base));
+        }
+        if self.is_default() {
+            return Ok(());
+        }
+        for &c in coords {
+            let (h, v) = self.split(c);
+            if h < self.base || v < self.base {
+                return Err(format!("Coordinate ({},{}) is below the declared base {}.", c.0, c.1, self.base));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Mapping between a file's declared convention and the internal one. A top-left origin needs the puzzle's vertical
+/// extent to flip rows, so the transform is built from the coordinates it will convert.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CoordTransform {
+    pub system: CoordSystem,
+    max_vertical: u32,
+}
+
+impl CoordTransform {
+    pub fn new<'a>(system: CoordSystem, coords: impl IntoIterator<Item = &'a Coord>) -> Self {
+        let max_vertical = coords.into_iter().map(|&c| system.split(c).1).max().unwrap_or(system.base);
+        CoordTransform { system, max_vertical }
+    }
+
+    /// For a top-left origin, move every internal row up by one if needed so that the declared first row is an odd
+    /// internal row. Row parity (which hex rows are shifted) then counts from the declared origin, whatever the height.
+    pub fn align_row_parity(mut self) -> Self {
+        if self.system.origin == Origin::TopLeft && (self.max_vertical + 1).saturating_sub(self.system.base).is_multiple_of(2) {
+            self.max_vertical += 1;
+        }
+        self
+    }
+
+    /// Convert a coordinate written in the declared convention to the internal one.
+    pub fn to_internal(self, c: Coord) -> Coord {
+        let (h, v) = self.system.split(c);
+        let base = self.system.base;
+        let y = match self.system.origin {
+            Origin::BottomLeft => (v + 1).saturating_sub(base),
+            Origin::TopLeft => self.max_vertical.saturating_sub(v) + 1,
+        };
+        ((h + 1).saturating_sub(base), y)
+    }
+
+    /// Convert an internal coordinate back to the declared convention.
+    pub fn to_user(self, c: Coord) -> Coord {
+        let base = self.system.base;
+        let v = match self.system.origin {
+            Origin::BottomLeft => (c.1 + base).saturating_sub(1),
+            Origin::TopLeft => (self.max_vertical + 1).saturating_sub(c.1),
+        };
+        self.system.join((c.0 + base).saturating_sub(1), v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_is_identity() {
+        let t = CoordTransform::new(CoordSystem::default(), &[(1, 1), (3, 4)]);
+        for c in [(0, 0), (1, 1), (3, 4)] {
+            assert_eq!(t.to_internal(c), c);
+            assert_eq!(t.to_user(c), c);
+        }
+    }
+
+    #[test]
+    fn top_left_zero_based_row_col() {
+        let system = CoordSystem { origin: Origin::TopLeft, base: 0, axes: Axes::RowCol };
+        // Three rows: row 0 is the top, row 2 the bottom.
+        let user = [(0, 0), (0, 2), (2, 0), (1, 1)];
+        let t = CoordTransform::new(system, &user);
+        assert_eq!(t.to_internal((0, 0)), (1, 3));
+        assert_eq!(t.to_internal((0, 2)), (3, 3));
+        assert_eq!(t.to_internal((2, 0)), (1, 1));
+        for c in user { assert_eq!(t.to_user(t.to_internal(c)), c); }
+        // Three rows put the top row on internal row 3 already; four would put it on row 4.
+        assert_eq!(t.align_row_parity(), t);
+        let taller = CoordTransform::new(system, &[(0, 0), (3, 0)]).align_row_parity();
+        assert_eq!((taller.to_internal((0, 0)), taller.to_internal((3, 0))), ((1, 5), (1, 2)));
+        assert_eq!(taller.to_user((1, 5)), (0, 0));
+    }
+
+    #[test]
+    fn validation() {
+        let zero = CoordSystem { base: 0, ..Default::default() };
+        assert!(zero.validate(&[(0, 0)]).is_ok());
+        assert!(CoordSystem::default().validate(&[(1, 0)]).is_ok());
+        assert!(CoordSystem { origin: Origin::TopLeft, ..Default::default() }.validate(&[(1, 0)]).is_err());
+        assert!(CoordSystem { base: 2, ..Default::default() }.validate(&[]).is_err());
+        let parsed: CoordSystem = serde_json::from_str(r#"{"origin": "top-left", "axes": "row-col"}"#).unwrap();
+        assert_eq!(parsed, CoordSystem { origin: Origin::TopLeft, base: 1, axes: Axes::RowCol });
+        assert!(serde_json::from_str::<CoordSystem>(r#"{"corner": "top-left"}"#).is_err());
+    }
+}
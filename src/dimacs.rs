@@ -0,0 +1,276 @@
+//! DIMACS CNF export for cross-checking against external SAT solvers, and import of the resulting model.
+//!
+//! Variables:
+//! - `pip(c, v)`: cell `c` shows `v` pips (`0..=max_pip` of the puzzle).
+//! - `place(d, a, b)`: inventory domino `d` lies on adjacent cells `a`, `b` with its first pip on `a`.
+//! - `sum(r, i, k)`: the first `i` cells of sum region `r` add up to `k` (auxiliary).
+//!
+//! Every cell is covered by exactly one placement, every domino is used at most once (the inventory may hold spares,
+//! as in the backtracking solver), each cell shows at most one pip value, and each region rule becomes clauses over the
+//! pip variables.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::grid::{Coord, GameGrid, Placement, Rule};
+
+/// A CNF formula plus the mapping from placement variables back to dominoes on the board.
+pub struct CnfEncoding {
+    pub num_vars: usize,
+    pub clauses: Vec<Vec<i64>>,
+    /// Placement per variable, keyed by variable number, with cells in the puzzle's declared convention.
+    placements: Vec<(i64, Placement)>,
+}
+
+struct Builder {
+    num_vars: usize,
+    clauses: Vec<Vec<i64>>,
+}
+
+impl Builder {
+    fn var(&mut self) -> i64 {
+        self.num_vars += 1;
+        self.num_vars as i64
+    }
+
+    fn at_most_one(&mut self, vars: &[i64]) {
+        for (i, &a) in vars.iter().enumerate() {
+            for &b in &vars[i + 1..] { self.clauses.push(vec![-a, -b]); }
+        }
+    }
+}
+
+/// Encode the puzzle's board, inventory and rules. The grid's current assignment is ignored.
+pub fn encode(g: &GameGrid) -> CnfEncoding {
+    let mut b = Builder { num_vars: 0, clauses: Vec::new() };
+    let values = g.max_pip as usize + 1;
+    let mut pip: HashMap<Coord, Vec<i64>> = HashMap::new();
+    for &c in &g.ordered_coords {
+        let vars: Vec<i64> = (0..values).map(|_| b.var()).collect();
+        b.at_most_one(&vars);
+        pip.insert(c, vars);
+    }
+
+    let mut placements = Vec::new();
+    let mut covering: HashMap<Coord, Vec<i64>> = HashMap::new();
+    for &(pa, pb) in &g.domino_inventory {
+        let mut uses = Vec::new();
+        for &a in &g.ordered_coords {
+            for &n in g.neighbors(a) {
+                // A double looks the same either way round, so only one orientation is needed.
+                if pa == pb && n < a { continue; }
+                let v = b.var();
+                b.clauses.push(vec![-v, pip[&a][pa as usize]]);
+                b.clauses.push(vec![-v, pip[&n][pb as usize]]);
+                covering.entry(a).or_default().push(v);
+                covering.entry(n).or_default().push(v);
+                uses.push(v);
+                placements.push((v, Placement { cells: [g.to_user(a), g.to_user(n)], pips: [pa, pb] }));
+            }
+        }
+        b.at_most_one(&uses);
+    }
+    for c in &g.ordered_coords {
+        let vars = covering.remove(c).unwrap_or_default();
+        b.at_most_one(&vars);
+        b.clauses.push(vars);
+    }
+
+    for (entry, &rule) in g.entries.iter().zip(&g.parsed_rules) {
+        let cells: Vec<&Vec<i64>> = entry.coords.iter().map(|c| &pip[c]).collect();
+        match rule {
+            Rule::Any | Rule::Unknown => {}
+            Rule::Equal => {
+                for pair in cells.windows(2) {
+                    for (&a, &b2) in pair[0].iter().zip(pair[1]) { b.clauses.push(vec![-a, b2]); }
+                }
+            }
+            Rule::NotEqual => {
+                // For every value, some cell must show something else.
+                for v in 0..values { b.clauses.push(cells.iter().map(|c| -c[v]).collect()); }
+            }
+            Rule::Sum(t) => encode_sum(&mut b, &cells, values, |k| k == t),
+            Rule::GreaterThan(t) => encode_sum(&mut b, &cells, values, |k| k > t),
+            Rule::LessThan(t) => encode_sum(&mut b, &cells, values, |k| k < t),
+            Rule::Range(lo, hi) => encode_sum(&mut b, &cells, values, |k| (lo..=hi).contains(&k)),
+            Rule::Odd => encode_sum(&mut b, &cells, values, |k| k % 2 == 1),
+            Rule::Even => encode_sum(&mut b, &cells, values, |k| k % 2 == 0),
+            Rule::AllDifferent => {
+                for v in 0..values {
+                    let shown: Vec<i64> = cells.iter().map(|c| c[v]).collect();
+                    b.at_most_one(&shown);
+                }
+            }
+            Rule::OneOf(mask) => {
+                for c in &cells {
+                    for v in (0..values).filter(|&v| !Rule::mask_contains(mask, v as u32)) { b.clauses.push(vec![-c[v]]); }
+                }
+            }
+        }
+    }
+    CnfEncoding { num_vars: b.num_vars, clauses: b.clauses, placements }
+}
+
+/// Sequential-counter encoding of a region sum: `sum(i, k)` is implied by `sum(i-1, k-v)` and `pip(c_i, v)`, and every
+/// total the rule does not allow is forbidden. Spurious true counters can only forbid more, so no at-most-one is needed.
+fn encode_sum(b: &mut Builder, cells: &[&Vec<i64>], values: usize, allowed: impl Fn(u32) -> bool) {
+    let mut prev: Vec<i64> = vec![b.var()]; // sum of zero cells is 0
+    b.clauses.push(vec![prev[0]]);
+    for cell in cells {
+        let next: Vec<i64> = (0..prev.len() + values - 1).map(|_| b.var()).collect();
+        for (k, &s) in prev.iter().enumerate() {
+            for v in 0..values { b.clauses.push(vec![-s, -cell[v], next[k + v]]); }
+        }
+        prev = next;
+    }
+    for (k, &s) in prev.iter().enumerate() {
+        if !allowed(k as u32) { b.clauses.push(vec![-s]); }
+    }
+}
+
+impl CnfEncoding {
+    /// Render as DIMACS CNF. Comment lines list the placement variables (`c place <var> <x1> <y1> <x2> <y2> <a> <b>`).
+    pub fn to_dimacs(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "c pips_solver CNF: {} placement variables", self.placements.len()).ok();
+        for (v, p) in &self.placements {
+            let [(x1, y1), (x2, y2)] = p.cells;
+            writeln!(out, "c place {v} {x1} {y1} {x2} {y2} {} {}", p.pips[0], p.pips[1]).ok();
+        }
+        writeln!(out, "p cnf {} {}", self.num_vars, self.clauses.len()).ok();
+        for clause in &self.clauses {
+            for lit in clause { write!(out, "{lit} ").ok(); }
+            out.push_str("0\n");
+        }
+        out
+    }
+
+    /// Map a SAT solver's model back to domino placements. Accepts competition output (`s ...` / `v ...` lines) or a
+    /// bare list of literals; literals not mentioned count as false.
+    pub fn decode_model(&self, model: &str) -> Result<Vec<Placement>, String> {
+        let mut truth = vec![false; self.num_vars + 1];
+        for line in model.lines().map(str::trim) {
+            if line.starts_with('c') || line.is_empty() { continue; }
+            if let Some(status) = line.strip_prefix('s') {
+                if status.trim() != "SATISFIABLE" { return Err(format!("Solver reported {}.", status.trim())); }
+                continue;
+            }
+            for tok in line.trim_start_matches('v').split_whitespace() {
+                let lit: i64 = tok.parse().map_err(|_| format!("Invalid literal '{tok}' in model."))?;
+                let var = lit.unsigned_abs() as usize;
+                if var > self.num_vars { return Err(format!("Variable {var} is out of range.")); }
+                if lit > 0 { truth[var] = true; }
+            }
+        }
+        Ok(self.placements.iter().filter(|(v, _)| truth[*v as usize]).map(|&(_, p)| p).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::GridFile;
+    use crate::grid::test_support;
+
+    fn fixture(name: &str) -> GameGrid { GameGrid::from_parsed(test_support::fixture(name)) }
+
+    /// Tiny DPLL with unit propagation, enough for the small fixtures.
+    fn dpll(clauses: &[Vec<i64>], assign: &mut Vec<i8>) -> bool {
+        loop {
+            let mut unit = None;
+            for clause in clauses {
+                let mut open = None;
+                let mut n_open = 0;
+                let mut sat = false;
+                for &lit in clause {
+                    match assign[lit.unsigned_abs() as usize] * lit.signum() as i8 {
+                        1 => { sat = true; break; }
+                        0 => { n_open += 1; open = Some(lit); }
+                        _ => {}
+                    }
+                }
+                if sat { continue; }
+                if n_open == 0 { return false; }
+                if n_open == 1 { unit = open; break; }
+            }
+            match unit {
+                Some(lit) => assign[lit.unsigned_abs() as usize] = lit.signum() as i8,
+                None => break,
+            }
+        }
+        let Some(var) = (1..assign.len()).find(|&v| assign[v] == 0) else { return true };
+        for value in [1, -1] {
+            let mut next = assign.clone();
+            next[var] = value;
+            if dpll(clauses, &mut next) { *assign = next; return true; }
+        }
+        false
+    }
+
+    fn sat_solve(cnf: &CnfEncoding) -> Option<String> {
+        let mut assign = vec![0i8; cnf.num_vars + 1];
+        if !dpll(&cnf.clauses, &mut assign) { return None; }
+        let lits: Vec<String> = (1..assign.len()).map(|v| if assign[v] > 0 { v.to_string() } else { format!("-{v}") }).collect();
+        Some(format!("s SATISFIABLE\nv {} 0\n", lits.join(" ")))
+    }
+
+    #[test]
+    fn sat_agrees_with_backtracking() {
+        for name in ["easy_grid.json", "medium_grid.json", "not_equal_grid.json", "unsolvable_grid.json", "double_twelve_grid.json", "hex_grid.json", "torus_grid.json"] {
+            let mut g = fixture(name);
+            let cnf = encode(&g);
+            let model = sat_solve(&cnf);
+            assert_eq!(model.is_some(), g.solve().is_some(), "{name}");
+            if let Some(model) = model {
+                let placements = cnf.decode_model(&model).unwrap();
+                g.apply_placements(&placements).unwrap();
+                let pips = g.occupied.iter().map(|(&c, &v)| (g.to_user(c), v)).collect();
+                assert!(g.check_assignment(&pips), "{name}: SAT model must satisfy every rule");
+            }
+        }
+    }
+
+    #[test]
+    fn rule_clauses_restrict_pips() {
+        // One cell pair, sum must exceed 10: only 5-6 and 6-6 would fit, and only 6-5 is available.
+        let parsed: GridFile = serde_json::from_str(r#"{"grid": [{"rule": ">10", "coords": [[1,1],[2,1]]}], "dominoes": [[6,5],[1,1]]}"#).unwrap();
+        let g = GameGrid::from_parsed(parsed);
+        let placements = encode(&g).decode_model(&sat_solve(&encode(&g)).unwrap()).unwrap();
+        assert_eq!(placements.len(), 1);
+        assert_eq!(placements[0].pips.iter().map(|&p| p as u32).sum::<u32>(), 11);
+    }
+
+    #[test]
+    fn combinator_rules_agree_with_backtracking() {
+        let cases = [
+            (r#"[{"rule": "distinct", "coords": [[1,1],[2,1],[3,1]]}, {"rule": "x", "coords": [[4,1]]}]"#, "[[2,2],[2,3]]"),
+            (r#"[{"rule": "distinct", "coords": [[1,1],[2,1],[3,1],[4,1]]}]"#, "[[2,2],[3,3]]"),
+            (r#"[{"rule": "{1,3}", "coords": [[1,1],[2,1]]}, {"rule": "odd", "coords": [[3,1],[4,1]]}]"#, "[[3,1],[2,3],[1,4]]"),
+            (r#"[{"rule": "9..10", "coords": [[1,1],[2,1]]}, {"rule": "even", "coords": [[3,1],[4,1]]}]"#, "[[6,4],[1,2],[5,1]]"),
+        ];
+        for (grid, dominoes) in cases {
+            let parsed: GridFile = serde_json::from_str(&format!(r#"{{"grid": {grid}, "dominoes": {dominoes}}}"#)).unwrap();
+            let mut g = GameGrid::from_parsed(parsed);
+            let cnf = encode(&g);
+            let model = sat_solve(&cnf);
+            assert_eq!(model.is_some(), g.solve().is_some(), "{grid}");
+            if let Some(model) = model {
+                g.apply_placements(&cnf.decode_model(&model).unwrap()).unwrap();
+                let pips = g.occupied.clone();
+                assert!(g.check_assignment(&pips), "{grid}: SAT model must satisfy every rule");
+            }
+        }
+    }
+
+    #[test]
+    fn dimacs_text_and_model_parsing() {
+        let g = fixture("not_equal_grid.json");
+        let cnf = encode(&g);
+        let text = cnf.to_dimacs();
+        assert!(text.contains(&format!("p cnf {} {}", cnf.num_vars, cnf.clauses.len())));
+        assert_eq!(text.lines().filter(|l| l.starts_with("c place")).count(), 2);
+        assert!(cnf.decode_model("s UNSATISFIABLE\n").is_err());
+        assert!(cnf.decode_model("v 1 x 0").is_err());
+        assert!(cnf.decode_model("1 -2 0").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,302 @@
+//! Line-oriented puzzle editor behind the `edit` subcommand.
+//!
+//! After every change the board is redrawn as in the text format (region letters, `+` for a cell outside every
+//! region) together with the rules, the domino inventory and a live solution count. Cells are written `x,y` in the
+//! puzzle's coordinate convention, or `x1,y1..x2,y2` for a rectangle (at most `MAX_RANGE` cells a side). Type `help`
+//! for the commands.
+
+use std::io::{self, BufRead, Write};
+
+use pips_solver::grid::{Coord, Domino, GameGrid, GridEntry, GridFile, canonical_rule};
+use pips_solver::solver::{Backtracking, SolveOptions, Solver};
+use pips_solver::{json_format, text_format};
+
+/// The live count stops here ...
+const COUNT_LIMIT: usize = 1000;
+/// ... or after this long.
+const COUNT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+const HELP: &str = "\
+Commands (cells as x,y or x1,y1..x2,y2):
+  on CELLS             add cells outside every region
+  off CELLS            remove cells from the board
+  paint R|new CELLS    move cells into region R (a letter from the drawing) or a new region
+  rule R TOKEN         set the rule of region R, e.g. 'rule A =' or 'rule B <7'
+  domino A-B ...       add dominoes to the inventory
+  undomino A-B ...     remove dominoes from the inventory
+  show                 redraw the board
+  solve                print the first solution
+  save [PATH]          write the puzzle (JSON, or the text format for .pips/.txt)
+  quit                 leave the editor
+";
+
+pub struct Editor {
+    file: GridFile,
+    path: String,
+    /// Changes since the last save.
+    dirty: bool,
+    /// `quit` was refused once because of unsaved changes.
+    warned: bool,
+}
+
+impl Editor {
+    /// Edit the puzzle at `path`, starting empty if the file does not exist yet.
+    pub fn open(path: &str) -> Result<Self, String> {
+        let file = if std::path::Path::new(path).exists() {
+            let file = GridFile::from_path(path).map_err(|e| e.to_string())?;
+            crate::warn_overlaps(&file, None);
+            file
+        } else {
+            GridFile::default()
+        };
+        Ok(Editor { file, path: path.to_string(), dirty: false, warned: false })
+    }
+
+    /// Read commands from `input` until `quit` or end of input.
+    pub fn run(&mut self, input: &mut dyn BufRead, out: &mut dyn Write) -> io::Result<()> {
+        write!(out, "{}", self.view())?;
+        let mut line = String::new();
+        loop {
+            write!(out, "edit> ")?;
+            out.flush()?;
+            line.clear();
+            if input.read_line(&mut line)? == 0 {
+                // There is no second `quit` to wait for at the end of input, so only say what is being lost.
+                writeln!(out)?;
+                if self.dirty { writeln!(out, "Unsaved changes discarded at end of input; 'save' before quitting to keep them.")?; }
+                return Ok(());
+            }
+            match self.execute(&line) {
+                Ok(Some(reply)) => write!(out, "{reply}")?,
+                Ok(None) => return Ok(()),
+                Err(message) => writeln!(out, "Error: {message}")?,
+            }
+        }
+    }
+
+    /// Run one command, returning the text to print or `None` to leave.
+    fn execute(&mut self, line: &str) -> Result<Option<String>, String> {
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else { return Ok(Some(String::new())) };
+        let args: Vec<&str> = words.collect();
+        match command {
+            "on" => self.cells_on(&parse_cells(&args)?),
+            "off" => self.cells_off(&parse_cells(&args)?),
+            "paint" => {
+                let (&region, cells) = args.split_first().ok_or("paint needs a region and cells")?;
+                self.paint(region, &parse_cells(cells)?)?;
+            }
+            "rule" => {
+                let (&region, rule) = args.split_first().ok_or("rule needs a region and a rule")?;
+                if rule.is_empty() { return Err("rule needs a rule token".into()); }
+                let index = self.region_index(region)?;
+                self.file.grid[index].rule = canonical_rule(&rule.join(" "), self.file.not_equal);
+            }
+            "domino" => {
+                let dominoes = parse_dominoes(&args)?;
+                self.file.dominoes.extend(dominoes);
+            }
+            "undomino" => {
+                for (a, b) in parse_dominoes(&args)? {
+                    let i = self.file.dominoes.iter().position(|&d| d == (a, b) || d == (b, a))
+                        .ok_or_else(|| format!("No domino {a}-{b} in the inventory."))?;
+                    self.file.dominoes.remove(i);
+                }
+            }
+            "show" => return Ok(Some(self.view())),
+            "solve" => return Ok(Some(self.solution())),
+            "save" => return self.save(args.first().copied()).map(Some),
+            "help" => return Ok(Some(HELP.to_string())),
+            "quit" | "exit" => {
+                if self.dirty && !self.warned {
+                    self.warned = true;
+                    return Ok(Some("Unsaved changes; 'save' first or 'quit' again to discard them.\n".into()));
+                }
+                return Ok(None);
+            }
+            _ => return Err(format!("Unknown command '{command}' (try 'help').")),
+        }
+        self.dirty = true;
+        self.warned = false;
+        Ok(Some(self.view()))
+    }
+
+    /// Index of the region drawn with `symbol`.
+    fn region_index(&self, symbol: &str) -> Result<usize, String> {
+        text_format::SYMBOLS.chars().take(self.file.grid.len()).position(|c| c.to_string() == symbol)
+            .ok_or_else(|| format!("No region '{symbol}' on the board."))
+    }
+
+    fn on_board(&self, c: &Coord) -> bool { self.file.all_cells().any(|d| d == c) }
+
+    fn cells_on(&mut self, cells: &[Coord]) {
+        for &c in cells {
+            if !self.on_board(&c) { self.file.cells.push(c); }
+        }
+    }
+
+    fn cells_off(&mut self, cells: &[Coord]) {
+        self.file.cells.retain(|c| !cells.contains(c));
+        for entry in &mut self.file.grid { entry.coords.retain(|c| !cells.contains(c)); }
+        self.file.grid.retain(|e| !e.coords.is_empty());
+    }
+
+    fn paint(&mut self, region: &str, cells: &[Coord]) -> Result<(), String> {
+        let index = if region == "new" {
+            if self.file.grid.len() == text_format::SYMBOLS.len() { return Err("No region letters left.".into()); }
+            self.file.grid.push(GridEntry { rule: "x".into(), coords: Vec::new(), ..Default::default() });
+            self.file.grid.len() - 1
+        } else {
+            self.region_index(region)?
+        };
+        self.file.cells.retain(|c| !cells.contains(c));
+        for (i, entry) in self.file.grid.iter_mut().enumerate() {
+            if i == index {
+                for &c in cells {
+                    if !entry.coords.contains(&c) { entry.coords.push(c); }
+                }
+            } else {
+                entry.coords.retain(|c| !cells.contains(c));
+            }
+        }
+        // Painting may empty other regions, which shifts the letters of later ones.
+        self.file.grid.retain(|e| !e.coords.is_empty());
+        Ok(())
+    }
+
+    /// The drawing plus a status line with the solution count.
+    fn view(&self) -> String {
+        let drawing = text_format::write(&self.file)
+            .unwrap_or_else(|e| format!("(cannot draw: {e})\n{}", json_format::write(&self.file)));
+        format!("{drawing}{}\n", self.status())
+    }
+
+    fn status(&self) -> String {
+        let cells = {
+            let mut all: Vec<&Coord> = self.file.all_cells().collect();
+            all.sort_unstable();
+            all.dedup();
+            all.len()
+        };
+        let summary = format!("{cells} cells, {} dominoes", self.file.dominoes.len());
+        if let Err(message) = self.file.validate() { return format!("{summary}: {message}"); }
+        if cells == 0 { return summary; }
+        let mut g = GameGrid::from_parsed(self.file.clone());
+        let opts = SolveOptions { max_solutions: Some(COUNT_LIMIT), timeout: Some(COUNT_TIMEOUT), ..Default::default() };
+        let stats = Backtracking.enumerate(&mut g, &opts, &mut |_| false);
+        match (stats.solutions, stats.timed_out) {
+            (n, true) => format!("{summary}: at least {n} solutions (gave up counting)"),
+            (COUNT_LIMIT, _) => format!("{summary}: {COUNT_LIMIT}+ solutions"),
+            (1, _) => format!("{summary}: 1 solution"),
+            (n, _) => format!("{summary}: {n} solutions"),
+        }
+    }
+
+    fn solution(&self) -> String {
+        if let Err(message) = self.file.validate() { return format!("Error: {message}\n"); }
+        let mut g = GameGrid::from_parsed(self.file.clone());
+        match g.solve() {
+            Some(_) => g.ascii_board(false),
+            None => "No solution found.\n".into(),
+        }
+    }
+
+    fn save(&mut self, path: Option<&str>) -> Result<String, String> {
+        self.file.validate()?;
+        let path = path.unwrap_or(&self.path).to_string();
+        let text = if text_format::is_text_path(&path) {
+            text_format::write(&self.file).map_err(|e| e.to_string())?
+        } else {
+            json_format::write(&self.file)
+        };
+        std::fs::write(&path, text).map_err(|e| format!("{path}: {e}"))?;
+        self.path = path;
+        self.dirty = false;
+        Ok(format!("Saved {}.\n", self.path))
+    }
+}
+
+/// Largest rectangle, in cells per side, that one `x1,y1..x2,y2` token may cover.
+const MAX_RANGE: u32 = 64;
+
+/// Parse `x,y` and `x1,y1..x2,y2` tokens.
+fn parse_cells(tokens: &[&str]) -> Result<Vec<Coord>, String> {
+    if tokens.is_empty() { return Err("Expected cells such as 1,1 or 1,1..3,2.".into()); }
+    let point = |t: &str| -> Option<Coord> {
+        let (x, y) = t.split_once(',')?;
+        Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+    };
+    let mut cells = Vec::new();
+    for &token in tokens {
+        let invalid = || format!("Invalid cell '{token}', expected e.g. 1,1 or 1,1..3,2.");
+        match token.split_once("..") {
+            Some((from, to)) => {
+                let ((x1, y1), (x2, y2)) = (point(from).ok_or_else(invalid)?, point(to).ok_or_else(invalid)?);
+                if x1.abs_diff(x2) >= MAX_RANGE || y1.abs_diff(y2) >= MAX_RANGE {
+                    return Err(format!("Range '{token}' is larger than {MAX_RANGE}x{MAX_RANGE} cells."));
+                }
+                for x in x1.min(x2)..=x1.max(x2) {
+                    for y in y1.min(y2)..=y1.max(y2) { cells.push((x, y)); }
+                }
+            }
+            None => cells.push(point(token).ok_or_else(invalid)?),
+        }
+    }
+    Ok(cells)
+}
+
+/// Parse `a-b` tokens.
+fn parse_dominoes(tokens: &[&str]) -> Result<Vec<Domino>, String> {
+    if tokens.is_empty() { return Err("Expected dominoes such as 3-5.".into()); }
+    tokens.iter().map(|t| {
+        t.split_once('-').and_then(|(a, b)| Some((a.parse().ok()?, b.parse().ok()?)))
+            .ok_or_else(|| format!("Invalid domino '{t}', expected e.g. 3-5."))
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(path: &str, script: &str) -> String {
+        let mut editor = Editor::open(path).unwrap();
+        let mut out = Vec::new();
+        editor.run(&mut script.as_bytes(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn build_and_save_a_puzzle() {
+        let path = std::env::temp_dir().join(format!("pips_edit_{}.json", std::process::id()));
+        let path = path.to_string_lossy().into_owned();
+        let _ = std::fs::remove_file(&path);
+        let out = session(&path, "on 1,1..2,2\npaint new 1,1 2,1\nrule A =\npaint new 1,2\nrule B 4\ndomino 2-2 4-1\nsave\nquit\n");
+        assert!(out.contains("board:\n++\nAA\n"), "{out}");
+        assert!(out.contains("4 cells, 2 dominoes: 1 solution"), "{out}");
+        let saved = GridFile::from_path(&path).unwrap();
+        assert_eq!(saved.grid.len(), 2);
+        assert_eq!(saved.grid[0].rule, "=");
+        assert_eq!(saved.cells, vec![(2, 2)]);
+        assert_eq!(saved.dominoes, vec![(2, 2), (4, 1)]);
+
+        // Reopening the saved file picks up where we left off.
+        let out = session(&path, "off 2,2\nundomino 1-4\nshow\nquit\nquit\n");
+        assert!(out.contains("3 cells, 1 dominoes: 0 solutions"), "{out}");
+        assert!(out.contains("Unsaved changes"));
+        let out = session(&path, "off 1,1\n");
+        assert!(out.ends_with("Unsaved changes discarded at end of input; 'save' before quitting to keep them.\n"), "{out}");
+        assert!(!session(&path, "show\n").contains("discarded"), "nothing to lose without changes");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn bad_commands_report_errors() {
+        let out = session("/nonexistent/new.json", "paint Q 1,1\non 1\nundomino 3-3\nfly\n");
+        assert_eq!(out.matches("Error:").count(), 4, "{out}");
+        assert!(out.contains("No region 'Q'"));
+        assert_eq!(parse_cells(&["1,1..2,3"]).unwrap().len(), 6);
+        assert_eq!(parse_cells(&["1,1..64,64"]).unwrap().len(), 64 * 64);
+        assert!(parse_cells(&["1,1..65,1"]).unwrap_err().contains("larger than 64x64"));
+        assert!(parse_cells(&["0,0..4000000000,4000000000"]).is_err());
+    }
+}
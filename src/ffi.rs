@@ -0,0 +1,225 @@
+//! C ABI for embedding the solver in-process; declared in `include/pips_solver.h`.
+//!
+//! Functions return a status code: `PIPS_OK`, `PIPS_ERROR` and `PIPS_UNSOLVABLE` match the CLI's exit codes for the
+//! same outcomes, and `PIPS_USAGE` (bad arguments) is specific to this API. A panic inside the library is caught and
+//! reported as `PIPS_ERROR`. The message of the last failure on the calling thread is available from
+//! `pips_last_error`. Strings returned to C are owned by the caller and released with `pips_string_free`.
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use crate::grid::{GameGrid, GridFile};
+use crate::json_format;
+use crate::solver::{self, SolveOptions, Solver};
+
+pub const PIPS_OK: c_int = 0;
+/// The puzzle could not be loaded, or the library panicked.
+pub const PIPS_ERROR: c_int = 1;
+/// The puzzle has no solution.
+pub const PIPS_UNSOLVABLE: c_int = 2;
+/// A null pointer, a string that is not UTF-8 or an unknown solver name. The CLI has no matching exit code.
+pub const PIPS_USAGE: c_int = 3;
+
+/// A loaded puzzle and its current (possibly empty) solution.
+pub struct PipsPuzzle {
+    file: GridFile,
+    grid: GameGrid,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn fail(status: c_int, message: &str) -> c_int {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = message);
+    status
+}
+
+/// Run the body of an exported function, turning a panic into `PIPS_ERROR` (the message goes to `pips_last_error`)
+/// and `fallback` as the return value, since unwinding into C is undefined behaviour.
+fn guard<T>(fallback: T, body: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        let message = payload.downcast_ref::<&str>().copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown panic");
+        fail(PIPS_ERROR, &format!("Internal error: {message}"));
+        fallback
+    })
+}
+
+/// Borrow a C string as UTF-8, or `None` for null.
+///
+/// # Safety
+/// `s` must be null or point to a NUL-terminated string.
+unsafe fn optional_str<'a>(s: *const c_char) -> Result<Option<&'a str>, c_int> {
+    if s.is_null() { return Ok(None); }
+    // SAFETY: non-null and NUL-terminated per the caller's contract.
+    unsafe { CStr::from_ptr(s) }.to_str().map(Some).map_err(|_| fail(PIPS_USAGE, "String is not valid UTF-8."))
+}
+
+/// # Safety
+/// `name` must be null or point to a NUL-terminated string.
+unsafe fn solver_named(name: *const c_char) -> Result<&'static dyn Solver, c_int> {
+    // SAFETY: forwarded from the caller.
+    match unsafe { optional_str(name) }? {
+        None => Ok(solver::SOLVERS[0]),
+        Some(name) => solver::by_name(name).ok_or_else(|| fail(PIPS_USAGE, &format!("Unknown solver '{name}'."))),
+    }
+}
+
+/// Load a puzzle from a JSON document and store a new handle in `*out`.
+///
+/// # Safety
+/// `json` must point to a NUL-terminated string and `out` to writable storage for a pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pips_puzzle_from_json(json: *const c_char, out: *mut *mut PipsPuzzle) -> c_int {
+    guard(PIPS_ERROR, || {
+        if out.is_null() { return fail(PIPS_USAGE, "Null output pointer."); }
+        // SAFETY: `json` is a C string per the contract above.
+        let json = match unsafe { optional_str(json) } {
+            Ok(Some(json)) => json,
+            Ok(None) => return fail(PIPS_USAGE, "Null puzzle string."),
+            Err(status) => return status,
+        };
+        let file = match GridFile::from_json(json) {
+            Ok(file) => file,
+            Err(message) => return fail(PIPS_ERROR, &message),
+        };
+        let grid = GameGrid::from_parsed(file.clone());
+        // SAFETY: `out` is non-null and writable per the contract above.
+        unsafe { *out = Box::into_raw(Box::new(PipsPuzzle { file, grid })) };
+        PIPS_OK
+    })
+}
+
+/// Solve the puzzle with the named backend (null for the default), keeping the solution in the handle.
+///
+/// # Safety
+/// `puzzle` must be a live handle from `pips_puzzle_from_json`; `solver` null or a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pips_solve(puzzle: *mut PipsPuzzle, solver: *const c_char) -> c_int {
+    guard(PIPS_ERROR, || {
+        // SAFETY: a live, exclusively used handle per the contract above.
+        let Some(puzzle) = (unsafe { puzzle.as_mut() }) else { return fail(PIPS_USAGE, "Null puzzle."); };
+        // SAFETY: forwarded from the caller.
+        let solver = match unsafe { solver_named(solver) } { Ok(s) => s, Err(status) => return status };
+        match solver.solve(&mut puzzle.grid, &SolveOptions::default()).0 {
+            Some(_) => PIPS_OK,
+            None => fail(PIPS_UNSOLVABLE, "No solution found."),
+        }
+    })
+}
+
+/// Count solutions, stopping after `limit` (0 for no limit), and store the count in `*count`. Clears any solution
+/// previously kept in the handle.
+///
+/// # Safety
+/// `puzzle` must be a live handle from `pips_puzzle_from_json`, `solver` null or a NUL-terminated string and `count`
+/// point to writable storage.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pips_count_solutions(puzzle: *mut PipsPuzzle, solver: *const c_char, limit: u64, count: *mut u64) -> c_int {
+    guard(PIPS_ERROR, || {
+        // SAFETY: a live, exclusively used handle per the contract above.
+        let Some(puzzle) = (unsafe { puzzle.as_mut() }) else { return fail(PIPS_USAGE, "Null puzzle."); };
+        if count.is_null() { return fail(PIPS_USAGE, "Null count pointer."); }
+        // SAFETY: forwarded from the caller.
+        let solver = match unsafe { solver_named(solver) } { Ok(s) => s, Err(status) => return status };
+        // A limit past `usize::MAX` (32-bit targets) cannot be reached anyway, so saturate rather than truncate.
+        let limit = usize::try_from(limit).unwrap_or(usize::MAX);
+        let opts = SolveOptions { max_solutions: (limit > 0).then_some(limit), ..Default::default() };
+        let stats = solver.enumerate(&mut puzzle.grid, &opts, &mut |_| false);
+        puzzle.grid.reset();
+        // SAFETY: non-null and writable per the contract above.
+        unsafe { *count = stats.solutions as u64 };
+        PIPS_OK
+    })
+}
+
+/// The puzzle and its current solution as the JSON document `--json` prints, or null on failure. Free the result
+/// with `pips_string_free`.
+///
+/// # Safety
+/// `puzzle` must be null or a live handle from `pips_puzzle_from_json`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pips_result_json(puzzle: *const PipsPuzzle) -> *mut c_char {
+    guard(ptr::null_mut(), || {
+        // SAFETY: a live handle per the contract above.
+        let Some(puzzle) = (unsafe { puzzle.as_ref() }) else { fail(PIPS_USAGE, "Null puzzle."); return ptr::null_mut(); };
+        let json = json_format::write_solved(&puzzle.file, &puzzle.grid.placements());
+        CString::new(json).map_or(ptr::null_mut(), CString::into_raw)
+    })
+}
+
+/// Message of the last failure on this thread (empty if none). Valid until the next call into the library.
+#[unsafe(no_mangle)]
+pub extern "C" fn pips_last_error() -> *const c_char {
+    guard(ptr::null(), || LAST_ERROR.with(|e| e.borrow().as_ptr()))
+}
+
+/// Release a handle. Null is ignored.
+///
+/// # Safety
+/// `puzzle` must be null or a handle from `pips_puzzle_from_json` that has not been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pips_puzzle_free(puzzle: *mut PipsPuzzle) {
+    guard((), || {
+        // SAFETY: ownership returns from C per the contract above.
+        if !puzzle.is_null() { drop(unsafe { Box::from_raw(puzzle) }); }
+    })
+}
+
+/// Release a string returned by the library. Null is ignored.
+///
+/// # Safety
+/// `s` must be null or a string from this library that has not been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn pips_string_free(s: *mut c_char) {
+    guard((), || {
+        // SAFETY: ownership returns from C per the contract above.
+        if !s.is_null() { drop(unsafe { CString::from_raw(s) }); }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_through_the_c_api() {
+        let json = CString::new(r#"{"grid": [{"rule": "=", "coords": [[1,1],[2,1]]}], "dominoes": [[2,2]]}"#).unwrap();
+        let mut puzzle = ptr::null_mut();
+        unsafe {
+            assert_eq!(pips_puzzle_from_json(json.as_ptr(), &mut puzzle), PIPS_OK);
+            let mut count = 0;
+            assert_eq!(pips_count_solutions(puzzle, ptr::null(), 0, &mut count), PIPS_OK);
+            assert_eq!(count, 1);
+            assert_eq!(pips_count_solutions(puzzle, ptr::null(), u64::MAX, &mut count), PIPS_OK);
+            assert_eq!(count, 1);
+            let dlx = CString::new("dlx").unwrap();
+            assert_eq!(pips_solve(puzzle, dlx.as_ptr()), PIPS_OK);
+            let result = pips_result_json(puzzle);
+            assert!(CStr::from_ptr(result).to_str().unwrap().contains("\"pips\": [2, 2]"));
+            pips_string_free(result);
+            let nope = CString::new("nope").unwrap();
+            assert_eq!(pips_solve(puzzle, nope.as_ptr()), PIPS_USAGE);
+            assert_eq!(CStr::from_ptr(pips_last_error()).to_str().unwrap(), "Unknown solver 'nope'.");
+            pips_puzzle_free(puzzle);
+
+            let bad = CString::new("{").unwrap();
+            assert_eq!(pips_puzzle_from_json(bad.as_ptr(), &mut puzzle), PIPS_ERROR);
+            assert_eq!(pips_puzzle_from_json(ptr::null(), &mut puzzle), PIPS_USAGE);
+        }
+    }
+
+    #[test]
+    fn panics_become_errors() {
+        assert_eq!(guard(PIPS_OK, || -> c_int { panic!("boom") }), PIPS_OK);
+        let message = unsafe { CStr::from_ptr(pips_last_error()) };
+        assert_eq!(message.to_str().unwrap(), "Internal error: boom");
+        assert!(guard(ptr::null::<c_char>(), || panic!("{}", 42)).is_null());
+        assert_eq!(unsafe { CStr::from_ptr(pips_last_error()) }.to_str().unwrap(), "Internal error: 42");
+    }
+}
@@ -1,23 +1,219 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
 use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
 
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::coords::{CoordSystem, CoordTransform};
+use crate::nyt;
+use crate::progress::{Branch, ProgressSink, Reporter};
+use crate::solver::{Backtracking, SolveOptions, Solver};
+use crate::text_format;
+use crate::topology::Topology;
+use crate::trace::{TraceEvent, TraceSink};
 
 pub type Coord = (u32, u32);
 pub type Domino = (u8, u8);
 
+/// Highest pip value on a domino half when a puzzle does not say (double-six set).
+pub const DEFAULT_MAX_PIP: u32 = 6;
+/// Largest `max_pip` a puzzle may declare (double-eighteen, the largest common set).
+pub const MAX_SUPPORTED_PIP: u32 = 18;
+
 /// Top-level JSON structure describing a puzzle: rule regions and the available domino set.
-#[derive(Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 pub struct GridFile {
     pub grid: Vec<GridEntry>,
     pub dominoes: Vec<Domino>,
+    /// Convention the coordinates above are written in (defaults to bottom-left origin, 1-based, `[x, y]`).
+    #[serde(default, skip_serializing_if = "CoordSystem::is_default")]
+    pub coordinates: CoordSystem,
+    /// Meaning of the `!=` rule token (defaults to every pip different, as in the NYT game).
+    #[serde(default, skip_serializing_if = "NotEqualMode::is_default")]
+    pub not_equal: NotEqualMode,
+    /// Highest pip value of the domino set, e.g. 9 or 12 for double-nine / double-twelve (default `DEFAULT_MAX_PIP`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_pip: Option<u32>,
+    /// Board cells that belong to no region. Region cells are on the board anyway and may be listed here too.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cells: Vec<Coord>,
+    /// Which cells are adjacent (defaults to the square grid, see `topology`).
+    #[serde(default, skip_serializing_if = "Topology::is_square")]
+    pub topology: Topology,
+}
+
+/// Meaning of the `!=` rule token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NotEqualMode {
+    /// No two pips in the region are identical (`Rule::AllDifferent`).
+    #[default]
+    AllDifferent,
+    /// Legacy meaning from files written before the fix: the pips are not all identical (`Rule::NotEqual`).
+    NotAllEqual,
+}
+
+impl NotEqualMode {
+    pub fn is_default(&self) -> bool { *self == NotEqualMode::default() }
 }
 
 /// One rule region with its textual rule and the list of coordinates it constrains.
-#[derive(Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 pub struct GridEntry {
+    #[serde(deserialize_with = "deserialize_rule")]
     pub rule: String, // parsed later into Rule
     pub coords: Vec<Coord>,
+    /// Sharing cells with other regions is intended; suppresses `GridFile::overlap_warnings`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub overlap: bool,
+}
+
+/// One placed domino of a solution: the two cells it covers and the pip shown on each.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Placement {
+    pub cells: [Coord; 2],
+    pub pips: [u8; 2],
+}
+
+impl GridFile {
+    /// Load a puzzle from disk, reading the text format for `.pips` / `.txt` files and JSON otherwise.
+    pub fn from_path(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let contents = fs::read_to_string(path)?;
+        if !text_format::is_text_path(path) { return Ok(Self::from_json(&contents)?); }
+        let parsed = text_format::parse(&contents)?;
+        parsed.validate()?;
+        Ok(parsed)
+    }
+
+    /// Parse and validate a JSON puzzle held in memory.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let parsed: GridFile = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        parsed.validate()?;
+        Ok(parsed)
+    }
+
+    /// Highest pip value of this puzzle's domino set.
+    pub fn max_pip(&self) -> u32 { self.max_pip.unwrap_or(DEFAULT_MAX_PIP) }
+
+    /// Sanity-check a loaded puzzle before it is handed to the solver.
+    pub fn validate(&self) -> Result<(), String> {
+        let max_pip = self.max_pip();
+        if max_pip > MAX_SUPPORTED_PIP {
+            return Err(format!("max_pip {max_pip} is above the supported maximum of {MAX_SUPPORTED_PIP}."));
+        }
+        if let Some(&(a, b)) = self.dominoes.iter().find(|&&(a, b)| a.max(b) as u32 > max_pip) {
+            return Err(format!("Domino {a}-{b} exceeds the maximum pip value {max_pip}."));
+        }
+        self.coordinates.validate(self.all_cells())?;
+        if !self.topology.is_square() {
+            let internal = self.normalised();
+            let cells: Vec<Coord> = internal.all_cells().copied().collect();
+            internal.topology.validate(&cells)?;
+        }
+        Ok(())
+    }
+
+    /// Every board cell: the explicit `cells` plus all region coordinates, possibly with repeats.
+    pub fn all_cells(&self) -> impl Iterator<Item = &Coord> {
+        self.cells.iter().chain(self.grid.iter().flat_map(|e| &e.coords))
+    }
+
+    /// Mapping between this file's declared coordinate convention and the internal one. Hex rows keep their parity
+    /// relative to the declared origin (see `CoordTransform::align_row_parity`).
+    pub fn transform(&self) -> CoordTransform {
+        let t = CoordTransform::new(self.coordinates, self.all_cells());
+        if self.topology == Topology::Hex { t.align_row_parity() } else { t }
+    }
+
+    /// One message per pair of regions that share cells without either being marked `"overlap": true`.
+    pub fn overlap_warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        for (i, a) in self.grid.iter().enumerate() {
+            for (j, b) in self.grid.iter().enumerate().skip(i + 1) {
+                if a.overlap || b.overlap { continue; }
+                if let Some(&(x, y)) = a.coords.iter().find(|c| b.coords.contains(c)) {
+                    warnings.push(format!("Regions {} ('{}') and {} ('{}') share cell ({x},{y}); mark one with \"overlap\": true if this is intended.",
+                        i + 1, a.rule, j + 1, b.rule));
+                }
+            }
+        }
+        warnings
+    }
+
+    /// Copy of this puzzle with every coordinate converted to the internal convention.
+    pub fn normalised(&self) -> GridFile {
+        let t = self.transform();
+        let grid = self.grid.iter().map(|e| GridEntry {
+            coords: e.coords.iter().map(|&c| t.to_internal(c)).collect(),
+            ..e.clone()
+        }).collect();
+        let cells = self.cells.iter().map(|&c| t.to_internal(c)).collect();
+        GridFile { grid, cells, coordinates: CoordSystem::default(), topology: self.topology.to_internal(t), ..self.clone() }
+    }
+}
+
+/// Lazily parse a stream of puzzles: either a single JSON document or several documents (e.g. JSON Lines),
+/// yielding each validated `GridFile` as soon as it has been read. Errors name the line the bad document starts on;
+/// reading then resumes at the next line starting with `{` in the first column, so one bad record does not end a
+/// JSON Lines stream.
+pub fn read_grid_files<R: Read>(reader: R) -> impl Iterator<Item = Result<GridFile, String>> {
+    let mut lines = BufReader::new(reader).lines();
+    let mut pending = String::new();
+    let mut first_line = 1usize; // line number of the start of `pending`
+    let mut line_no = 0usize;
+    let mut skipping = false;
+    let mut broken = false; // a read error ends the stream
+    let mut ready: VecDeque<Result<GridFile, String>> = VecDeque::new();
+    std::iter::from_fn(move || loop {
+        if let Some(item) = ready.pop_front() { return Some(item); }
+        if broken { return None; }
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            Some(Err(e)) => { broken = true; return Some(Err(e.to_string())); }
+            None if pending.trim().is_empty() => return None,
+            None => {
+                let message = serde_json::from_str::<GridFile>(&pending).err().map_or_else(|| "incomplete document".to_string(), |e| e.to_string());
+                pending.clear();
+                return Some(Err(format!("line {first_line}: {message}")));
+            }
+        };
+        line_no += 1;
+        if skipping && !line.starts_with('{') { continue; }
+        skipping = false;
+        if pending.trim().is_empty() {
+            pending.clear();
+            first_line = line_no;
+        }
+        pending.push_str(&line);
+        pending.push('\n');
+        let mut stream = serde_json::Deserializer::from_str(&pending).into_iter::<GridFile>();
+        let mut consumed = 0;
+        loop {
+            let start = consumed;
+            let at = first_line + pending[..start].matches('\n').count();
+            match stream.next() {
+                Some(Ok(g)) => {
+                    consumed = stream.byte_offset();
+                    ready.push_back(g.validate().map(|()| g).map_err(|e| format!("line {at}: {e}")));
+                }
+                // The document continues on the next line.
+                Some(Err(e)) if e.is_eof() => break,
+                Some(Err(e)) => {
+                    ready.push_back(Err(format!("line {}: {e}", first_line + e.line() - 1)));
+                    consumed = pending.len();
+                    skipping = true;
+                    break;
+                }
+                None => { consumed = pending.len(); break; }
+            }
+        }
+        first_line += pending[..consumed].matches('\n').count();
+        pending.drain(..consumed);
+    })
 }
 
 /// Normalised internal representation of rule semantics extracted from the JSON string.
@@ -25,7 +221,8 @@ pub struct GridEntry {
 pub enum Rule {
     /// All pips in the region must be identical.
     Equal,
-    /// Not all pips in the region may be identical (i.e. at least one difference). Token: "!=".
+    /// Not all pips in the region may be identical (i.e. at least one difference). Token: "!=" in files declaring
+    /// `"not_equal": "not-all-equal"`.
     NotEqual,
     /// Sum of all pips equals the target value.
     Sum(u32),
@@ -33,6 +230,16 @@ pub enum Rule {
     GreaterThan(u32),
     /// Sum of all pips strictly less than value.
     LessThan(u32),
+    /// Sum of all pips within the inclusive range. Token: "3..7".
+    Range(u32, u32),
+    /// Sum of all pips is odd. Token: "odd".
+    Odd,
+    /// Sum of all pips is even. Token: "even".
+    Even,
+    /// No two pips in the region are identical. Token: "distinct".
+    AllDifferent,
+    /// Every pip is one of the listed values, stored as a bitmask (bit `v` set for value `v`). Token: "{1,3,5}".
+    OneOf(u32),
     /// Unconstrained region ("x").
     Any,
     /// Unrecognised / unsupported rule token (treated as unconstrained for now).
@@ -40,28 +247,128 @@ pub enum Rule {
 }
 
 impl Rule {
-    /// Parse a raw rule string (e.g. "=", "6", ">2", "<6", "x") into a `Rule` value.
-    fn parse(s: &str) -> Self {
-        if s == "=" {
-            return Rule::Equal;
+    /// Parse a raw rule string (e.g. "=", "6", ">2", "<6", "x", "2..5", "odd", "{1,3}") into a `Rule` value.
+    /// `not_equal` decides what "!=" means. The grammar is tolerant: case and surrounding whitespace are ignored and
+    /// spellings found in other sources are accepted ("≠", "∑=5", "sum:5", "any", "equal", "unequal", ...).
+    pub(crate) fn parse(s: &str, not_equal: NotEqualMode) -> Self {
+        let lower = s.trim().to_lowercase();
+        let s = lower.as_str();
+        match s {
+            "=" | "==" | "equal" | "equals" => return Rule::Equal,
+            "!=" | "≠" | "unequal" | "not-equal" => {
+                return match not_equal {
+                    NotEqualMode::AllDifferent => Rule::AllDifferent,
+                    NotEqualMode::NotAllEqual => Rule::NotEqual,
+                };
+            }
+            "not-all-equal" => return Rule::NotEqual,
+            "x" | "any" | "empty" => return Rule::Any,
+            "odd" => return Rule::Odd,
+            "even" => return Rule::Even,
+            "distinct" | "all-different" => return Rule::AllDifferent,
+            _ => {}
         }
-        if s == "!=" {
-            return Rule::NotEqual;
+        // "∑=5", "Σ5", "sum:5", "sum = 3..7": a sum prefix in front of a sum, range or bound.
+        for prefix in ["∑", "σ", "sum"] {
+            if let Some(rest) = s.strip_prefix(prefix) {
+                let rest = rest.trim_start().strip_prefix([':', '=']).unwrap_or(rest);
+                return match Self::parse(rest, not_equal) {
+                    r @ (Rule::Sum(_) | Rule::Range(..) | Rule::GreaterThan(_) | Rule::LessThan(_)) => r,
+                    _ => Rule::Unknown,
+                };
+            }
         }
-        if s == "x" {
-            return Rule::Any;
+        if let Some((lo, hi)) = s.split_once("..") {
+            return match (lo.trim().parse(), hi.trim().parse()) {
+                (Ok(lo), Ok(hi)) if lo <= hi => Rule::Range(lo, hi),
+                _ => Rule::Unknown,
+            };
+        }
+        if let Some(list) = s.strip_prefix('{').and_then(|r| r.strip_suffix('}')) {
+            let mut mask = 0u32;
+            for v in list.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+                match v.parse::<u32>() {
+                    Ok(v) if v < u32::BITS => mask |= 1 << v,
+                    _ => return Rule::Unknown,
+                }
+            }
+            return Rule::OneOf(mask);
         }
         if let Some(num) = s.strip_prefix('>') {
-            return num.parse().map(Rule::GreaterThan).unwrap_or(Rule::Unknown);
+            return num.trim().parse().map(Rule::GreaterThan).unwrap_or(Rule::Unknown);
         }
         if let Some(num) = s.strip_prefix('<') {
-            return num.parse().map(Rule::LessThan).unwrap_or(Rule::Unknown);
+            return num.trim().parse().map(Rule::LessThan).unwrap_or(Rule::Unknown);
         }
         if let Ok(v) = s.parse() {
             return Rule::Sum(v);
         }
         Rule::Unknown
     }
+
+    /// Canonical token for this rule in a file using `not_equal`; parsing it back gives the same `Rule`.
+    pub fn token(self, not_equal: NotEqualMode) -> String {
+        match (self, not_equal) {
+            (Rule::AllDifferent, NotEqualMode::AllDifferent) | (Rule::NotEqual, NotEqualMode::NotAllEqual) => "!=".into(),
+            (Rule::AllDifferent, _) => "distinct".into(),
+            (Rule::NotEqual, _) => "not-all-equal".into(),
+            (Rule::Equal, _) => "=".into(),
+            (Rule::Sum(t), _) => t.to_string(),
+            (Rule::GreaterThan(t), _) => format!(">{t}"),
+            (Rule::LessThan(t), _) => format!("<{t}"),
+            (Rule::Range(lo, hi), _) => format!("{lo}..{hi}"),
+            (Rule::Odd, _) => "odd".into(),
+            (Rule::Even, _) => "even".into(),
+            (Rule::OneOf(mask), _) => {
+                let values: Vec<String> = (0..u32::BITS).filter(|v| mask & (1 << v) != 0).map(|v| v.to_string()).collect();
+                format!("{{{}}}", values.join(","))
+            }
+            (Rule::Any, _) => "x".into(),
+            (Rule::Unknown, _) => "?".into(),
+        }
+    }
+
+    /// Whether a `OneOf` mask contains pip `v`; values past the mask's 32 bits never do.
+    pub(crate) fn mask_contains(mask: u32, v: u32) -> bool {
+        mask.checked_shr(v).is_some_and(|m| m & 1 == 1)
+    }
+}
+
+/// Canonical spelling of a raw rule string; unrecognised rules are kept as written.
+pub fn canonical_rule(raw: &str, not_equal: NotEqualMode) -> String {
+    match Rule::parse(raw, not_equal) {
+        Rule::Unknown => raw.to_string(),
+        rule => rule.token(not_equal),
+    }
+}
+
+impl fmt::Display for Rule {
+    /// The token under the default `!=` meaning.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.token(NotEqualMode::default()))
+    }
+}
+
+/// A rule as written in JSON: a token string, a bare number (a sum target) or an object using the NYT region type
+/// names, e.g. `{"type": "sum", "target": 5}`. All are stored as the token string.
+fn deserialize_rule<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged, expecting = "a rule token, a number or an object like {\"type\": \"sum\", \"target\": 5}")]
+    enum RawRule {
+        Token(String),
+        Number(u32),
+        Structured {
+            #[serde(rename = "type")]
+            kind: String,
+            #[serde(default)]
+            target: Option<u32>,
+        },
+    }
+    match RawRule::deserialize(deserializer)? {
+        RawRule::Token(token) => Ok(token),
+        RawRule::Number(target) => Ok(target.to_string()),
+        RawRule::Structured { kind, target } => nyt::rule_token(&kind, target).map_err(serde::de::Error::custom),
+    }
 }
 
 /// Internal evaluation state for a region while solving.
@@ -75,63 +382,91 @@ enum RegionState {
 /// In-memory puzzle grid plus solver state (current assignments & remaining dominoes).
 pub struct GameGrid {
     pub entries: Vec<GridEntry>,
-    pub rule_index: HashMap<Coord, String>, // original string rules by coord
+    pub rule_index: HashMap<Coord, Vec<String>>, // board cell -> original rule strings of every region containing it
     pub occupied: HashMap<Coord, u8>,       // now stores pip value per cell
     // Parsed & derived data:
-    parsed_rules: Vec<Rule>,                   // parallel to entries
+    pub(crate) parsed_rules: Vec<Rule>,        // parallel to entries
     coord_regions: HashMap<Coord, Vec<usize>>, // coord -> indices of entries
-    domino_inventory: Vec<Domino>,             // remaining dominoes
+    pub(crate) domino_inventory: Vec<Domino>,  // full domino set, in file order
+    domino_used: Vec<bool>,                    // parallel to domino_inventory: placed on the board
     domino_ids: HashMap<Coord, usize>, // new: track which domino each coord belongs to
-    ordered_coords: Vec<Coord>, // deterministic ordering of coordinates
+    pub(crate) ordered_coords: Vec<Coord>, // deterministic ordering of coordinates
+    coord_transform: CoordTransform, // maps internal coords back to the file's convention
+    pub(crate) nodes: u64, // search nodes visited by the last solve
+    pub(crate) node_limit: u64, // the search gives up once `nodes` exceeds this
+    pub(crate) deadline: Option<Instant>, // ... or once this time has passed
+    pub(crate) timed_out: bool, // the last search stopped at `deadline`
+    pub(crate) interrupted: bool, // ... or because `stop` was set
+    pub(crate) max_pip: u32, // highest pip value in the domino set
+    pub(crate) topology: Topology,
+    adjacency: HashMap<Coord, Vec<Coord>>, // board cell -> adjacent board cells, from `topology`
+    trace: Option<TraceSink>, // receives every search step when set (see `trace`)
+    pub(crate) branches: Vec<Branch>, // decision path of the running search, one entry per level
+    pub(crate) resume: Vec<usize>, // the next search skips the choices before this path (see `resume_at`)
+    stop: Option<Arc<AtomicBool>>, // the search gives up once this is set
+    pub(crate) progress: Option<Reporter>, // receives periodic reports when set (see `progress`)
 }
 
 impl GameGrid {
-    /// Load a `GameGrid` from a JSON file on disk.
+    /// Load a `GameGrid` from a puzzle file on disk (JSON or the text format, see `GridFile::from_path`).
     pub fn from_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let json: String = fs::read_to_string(path)?;
-        let parsed: GridFile = serde_json::from_str(&json)?;
-        Ok(Self::from_parsed(parsed))
+        Ok(Self::from_parsed(GridFile::from_path(path)?))
     }
 
     /// Construct from an already deserialized `GridFile`, building indices used by the solver.
+    /// Coordinates are normalised to the internal bottom-left `(1,1)` convention (see `coords`). The file is not
+    /// validated; a domino above `max_pip` raises the maximum to its pip so pruning and encodings stay sound.
     pub fn from_parsed(parsed: GridFile) -> Self {
-        let mut rule_index = HashMap::new();
+        let coord_transform = parsed.transform();
+        let parsed = if parsed.coordinates.is_default() { parsed } else { parsed.normalised() };
+        let largest = parsed.dominoes.iter().map(|&(a, b)| a.max(b) as u32).max().unwrap_or(0);
+        let max_pip = parsed.max_pip().max(largest);
+        let mut rule_index: HashMap<Coord, Vec<String>> = parsed.cells.iter().map(|&c| (c, Vec::new())).collect();
         let mut parsed_rules = Vec::with_capacity(parsed.grid.len());
         let mut coord_regions: HashMap<Coord, Vec<usize>> = HashMap::new();
-    let mut coord_set: HashSet<Coord> = HashSet::new();
+    let mut coord_set: HashSet<Coord> = parsed.cells.iter().copied().collect();
         for (i, entry) in parsed.grid.iter().enumerate() {
-            let r = Rule::parse(&entry.rule);
+            let r = Rule::parse(&entry.rule, parsed.not_equal);
             parsed_rules.push(r);
             for &c in &entry.coords {
-                rule_index.insert(c, entry.rule.clone());
+                rule_index.entry(c).or_default().push(entry.rule.clone());
                 coord_regions.entry(c).or_default().push(i);
         coord_set.insert(c);
             }
         }
     let mut ordered_coords: Vec<Coord> = coord_set.into_iter().collect();
     ordered_coords.sort_unstable();
+        let adjacency = parsed.topology.adjacency(&ordered_coords);
         GameGrid {
             entries: parsed.grid,
             rule_index,
             occupied: HashMap::new(),
             parsed_rules,
             coord_regions,
+            domino_used: vec![false; parsed.dominoes.len()],
             domino_inventory: parsed.dominoes,
             domino_ids: HashMap::new(),
         ordered_coords,
+            coord_transform,
+            nodes: 0,
+            node_limit: u64::MAX,
+            deadline: None,
+            timed_out: false,
+            interrupted: false,
+            max_pip,
+            topology: parsed.topology,
+            adjacency,
+            trace: None,
+            branches: Vec::new(),
+            resume: Vec::new(),
+            stop: None,
+            progress: None,
         }
     }
 
-    /// Return orthogonally adjacent coordinates (wrapping subtraction safe for x/y=0).
-    pub fn neighbors(coord: Coord) -> impl Iterator<Item = Coord> {
-        let (x, y) = coord;
-        [
-            (x.wrapping_sub(1), y),
-            (x + 1, y),
-            (x, y.wrapping_sub(1)),
-            (x, y + 1),
-        ]
-        .into_iter()
+    /// Board cells adjacent to `coord` under the puzzle's topology, in sorted order (empty off the board).
+    pub fn neighbors(&self, coord: Coord) -> &[Coord] {
+        self.adjacency.get(&coord).map_or(&[], Vec::as_slice)
     }
 
     /// Determine current state (Incomplete / Satisfied / Violated) of region `idx`.
@@ -182,7 +517,7 @@ impl GameGrid {
                 }
                 if values.len() >= 2 && values.iter().any(|&v| v != values[0]) {
                     // Already have a difference; rule permanently satisfied regardless of remaining empties.
-                    if empty == 0 { RegionState::Satisfied } else { RegionState::Satisfied }
+                    RegionState::Satisfied
                 } else if empty == 0 {
                     // All filled and all equal -> violation.
                     RegionState::Violated
@@ -194,7 +529,7 @@ impl GameGrid {
                 if sum > target {
                     return RegionState::Violated;
                 }
-                let max_possible = sum + (empty as u32) * 6;
+                let max_possible = sum + (empty as u32) * self.max_pip;
                 if max_possible < target {
                     return RegionState::Violated;
                 }
@@ -209,7 +544,7 @@ impl GameGrid {
                 }
             }
             Rule::GreaterThan(k) => {
-                let max_possible = sum + (empty as u32) * 6;
+                let max_possible = sum + (empty as u32) * self.max_pip;
                 if max_possible <= k {
                     return RegionState::Violated;
                 }
@@ -237,19 +572,52 @@ impl GameGrid {
                     RegionState::Incomplete
                 }
             }
+            Rule::Range(lo, hi) => {
+                if sum > hi || sum + (empty as u32) * self.max_pip < lo {
+                    RegionState::Violated
+                } else if empty == 0 {
+                    RegionState::Satisfied
+                } else {
+                    RegionState::Incomplete
+                }
+            }
+            Rule::Odd | Rule::Even => {
+                // Parity stays open until the last cell is filled.
+                if empty > 0 {
+                    RegionState::Incomplete
+                } else if (sum % 2 == 1) == (rule == Rule::Odd) {
+                    RegionState::Satisfied
+                } else {
+                    RegionState::Violated
+                }
+            }
+            Rule::AllDifferent => {
+                if values.iter().enumerate().any(|(i, v)| values[..i].contains(v)) {
+                    return RegionState::Violated;
+                }
+                if empty == 0 { RegionState::Satisfied } else { RegionState::Incomplete }
+            }
+            Rule::OneOf(mask) => {
+                if values.iter().any(|&v| !Rule::mask_contains(mask, v as u32)) {
+                    RegionState::Violated
+                } else if empty == 0 {
+                    RegionState::Satisfied
+                } else {
+                    RegionState::Incomplete
+                }
+            }
         }
     }
 
     /// Check that every region touching any of the provided coordinates is still feasible.
-    fn affected_regions_feasible(&self, coords: &[Coord]) -> bool {
+    pub(crate) fn affected_regions_feasible(&mut self, coords: &[Coord]) -> bool {
         let mut seen = std::collections::HashSet::new();
         for &c in coords {
             if let Some(indices) = self.coord_regions.get(&c) {
                 for &idx in indices {
-                    if seen.insert(idx) {
-                        if matches!(self.region_state(idx), RegionState::Violated) {
-                            return false;
-                        }
+                    if seen.insert(idx) && matches!(self.region_state(idx), RegionState::Violated) {
+                        self.emit(|g| TraceEvent::Prune { region: idx, rule: g.entries[idx].rule.clone() });
+                        return false;
                     }
                 }
             }
@@ -257,24 +625,181 @@ impl GameGrid {
         true
     }
 
+    /// With every cell covered, whether all regions meet their rules.
+    pub(crate) fn all_regions_satisfied(&mut self) -> bool {
+        match (0..self.entries.len()).find(|&i| !matches!(self.region_state(i), RegionState::Satisfied)) {
+            Some(idx) => {
+                self.emit(|g| TraceEvent::Prune { region: idx, rule: g.entries[idx].rule.clone() });
+                false
+            }
+            None => {
+                self.emit(|_| TraceEvent::Solution);
+                true
+            }
+        }
+    }
+
+    /// Send every following search step to `sink`, or stop tracing with `None`.
+    pub fn set_trace(&mut self, sink: Option<TraceSink>) { self.trace = sink; }
+
+    /// Send a progress report to `sink` every `every` while searching, or stop reporting with `None`.
+    pub fn set_progress(&mut self, sink: Option<ProgressSink>, every: std::time::Duration) {
+        self.progress = sink.map(|sink| Reporter::new(sink, every));
+    }
+
+    /// Abort searches once `flag` is set (read every 1024 nodes), e.g. from a Ctrl-C handler; `None` removes it.
+    pub fn set_stop_flag(&mut self, flag: Option<Arc<AtomicBool>>) { self.stop = flag; }
+
+    /// Where the last aborted search stopped: the choice index taken at each level on the way to the first node it
+    /// did not explore. Everything before that node in search order was explored.
+    pub fn search_path(&self) -> Vec<usize> { self.branches.iter().map(|b| b.index).collect() }
+
+    /// Make the next search (same solver, same starting board) continue from `path`, a `search_path` of an earlier
+    /// one, skipping everything that search already explored.
+    pub fn resume_at(&mut self, path: Vec<usize>) { self.resume = path; }
+
+    /// Report a search step if tracing; `event` is only built when needed.
+    fn emit(&mut self, event: impl FnOnce(&Self) -> TraceEvent) {
+        if let Some(mut sink) = self.trace.take() {
+            sink(&event(self));
+            self.trace = Some(sink);
+        }
+    }
+
     /// Attempt to solve the puzzle, returning a map of coordinate -> pip value on success.
+    /// Coordinates are in the puzzle file's declared convention.
+    /// Uses the default backtracking solver; see `solver` for the alternatives.
     pub fn solve(&mut self) -> Option<HashMap<Coord, u8>> {
-        if self.backtrack() {
-            Some(self.occupied.clone())
-        } else {
-            None
+        Backtracking.solve(self, &SolveOptions::default()).0
+    }
+
+    /// The current assignment with coordinates in the file's convention.
+    pub fn assignment(&self) -> HashMap<Coord, u8> {
+        self.occupied.iter().map(|(&c, &v)| (self.to_user(c), v)).collect()
+    }
+
+    /// Placed dominoes of the current assignment in inventory order, with cells in the file's convention.
+    pub fn placements(&self) -> Vec<Placement> {
+        let mut by_domino: HashMap<usize, Vec<Coord>> = HashMap::new();
+        for (&c, &id) in &self.domino_ids { by_domino.entry(id).or_default().push(c); }
+        let mut ids: Vec<usize> = by_domino.keys().copied().collect();
+        ids.sort_unstable();
+        ids.into_iter().filter_map(|id| {
+            let mut cells = by_domino.remove(&id)?;
+            cells.sort_unstable();
+            let [a, b] = cells[..] else { return None };
+            Some(Placement { cells: [self.to_user(a), self.to_user(b)], pips: [self.occupied[&a], self.occupied[&b]] })
+        }).collect()
+    }
+
+    /// Load an externally produced solution (cells in the file's convention), replacing the current assignment.
+    /// Each placement must cover two adjacent, distinct puzzle cells and use a distinct domino from the inventory.
+    pub fn apply_placements(&mut self, placements: &[Placement]) -> Result<(), String> {
+        self.reset();
+        for p in placements {
+            let [a, b] = p.cells.map(|c| self.coord_transform.to_internal(c));
+            let [ua, ub] = p.cells;
+            if !self.rule_index.contains_key(&a) || !self.rule_index.contains_key(&b) {
+                return Err(format!("Placement ({},{})-({},{}) leaves the board.", ua.0, ua.1, ub.0, ub.1));
+            }
+            if !self.neighbors(a).contains(&b) {
+                return Err(format!("Cells ({},{}) and ({},{}) are not adjacent.", ua.0, ua.1, ub.0, ub.1));
+            }
+            if self.occupied.contains_key(&a) || self.occupied.contains_key(&b) {
+                return Err(format!("Placement ({},{})-({},{}) overlaps another domino.", ua.0, ua.1, ub.0, ub.1));
+            }
+            let [pa, pb] = p.pips;
+            let id = (0..self.domino_inventory.len())
+                .find(|&i| !self.domino_used[i] && (self.domino_inventory[i] == (pa, pb) || self.domino_inventory[i] == (pb, pa)))
+                .ok_or_else(|| format!("No unused domino {pa}-{pb} in the inventory."))?;
+            self.domino_used[id] = true;
+            self.occupied.insert(a, pa);
+            self.occupied.insert(b, pb);
+            self.domino_ids.insert(a, id);
+            self.domino_ids.insert(b, id);
+        }
+        Ok(())
+    }
+
+    /// Load `placements` (as `apply_placements`) and check that they form a complete solution obeying every region.
+    pub fn verify_placements(&mut self, placements: &[Placement]) -> Result<(), String> {
+        self.apply_placements(placements)?;
+        if !self.check_assignment(&self.assignment()) {
+            return Err("Solution is incomplete or breaks a region rule.".into());
         }
+        Ok(())
+    }
+
+    /// Whether inventory domino `i` is still available.
+    pub(crate) fn domino_free(&self, i: usize) -> bool { !self.domino_used[i] }
+
+    /// Put inventory domino `domino` on cells `a` and `b` showing `pa` and `pb`.
+    pub(crate) fn place(&mut self, domino: usize, a: Coord, b: Coord, pa: u8, pb: u8) {
+        self.occupied.insert(a, pa);
+        self.occupied.insert(b, pb);
+        self.domino_ids.insert(a, domino);
+        self.domino_ids.insert(b, domino);
+        self.domino_used[domino] = true;
+        self.emit(|g| TraceEvent::Place { domino, cells: [g.to_user(a), g.to_user(b)], pips: [pa, pb] });
+    }
+
+    /// Undo `place` for the domino covering `a` and `b`.
+    pub(crate) fn unplace(&mut self, a: Coord, b: Coord) {
+        self.occupied.remove(&a);
+        self.occupied.remove(&b);
+        if let Some(id) = self.domino_ids.remove(&a) { self.domino_used[id] = false; }
+        self.domino_ids.remove(&b);
+        self.emit(|g| TraceEvent::Backtrack { cells: [g.to_user(a), g.to_user(b)] });
+    }
+
+    /// Clear the current assignment and return every domino to the inventory.
+    pub fn reset(&mut self) {
+        self.occupied.clear();
+        self.domino_ids.clear();
+        self.domino_used.iter_mut().for_each(|u| *u = false);
+    }
+
+    /// Convert an internal coordinate back to the puzzle file's declared convention.
+    pub fn to_user(&self, c: Coord) -> Coord { self.coord_transform.to_user(c) }
+
+    /// Convert a coordinate in the puzzle file's convention to the internal one.
+    pub fn to_internal(&self, c: Coord) -> Coord { self.coord_transform.to_internal(c) }
+
+    /// Check a complete pip assignment (e.g. an external solution, in the file's convention) against every region rule.
+    /// Every cell must have a value; the domino tiling itself is not checked.
+    pub fn check_assignment(&mut self, pips: &HashMap<Coord, u8>) -> bool {
+        let pips: HashMap<Coord, u8> = pips.iter().map(|(&c, &v)| (self.coord_transform.to_internal(c), v)).collect();
+        if pips.len() != self.rule_index.len() || !self.rule_index.keys().all(|c| pips.contains_key(c)) {
+            return false;
+        }
+        let saved = std::mem::replace(&mut self.occupied, pips);
+        let ok = (0..self.entries.len()).all(|i| matches!(self.region_state(i), RegionState::Satisfied));
+        self.occupied = saved;
+        ok
+    }
+
+    /// Count a search node; `true` once the node budget or the time is spent and the search should stop.
+    /// The clock is only read every 1024 nodes, which is also when progress is reported.
+    pub(crate) fn visit_node(&mut self) -> bool {
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(1024) {
+            if self.deadline.is_some_and(|d| Instant::now() >= d) { self.timed_out = true; }
+            if self.stop.as_ref().is_some_and(|f| f.load(Ordering::Relaxed)) { self.interrupted = true; }
+            if let Some(progress) = &mut self.progress { progress.tick(self.nodes, &self.branches, false); }
+        }
+        // A resumed search is back where it left off.
+        if self.branches.len() == self.resume.len() { self.resume.clear(); }
+        self.timed_out || self.interrupted || self.nodes > self.node_limit
     }
 
     /// Recursive backtracking search with forward-checking (region feasibility pruning).
-    fn backtrack(&mut self) -> bool {
+    /// `on_solution` sees each complete solution and returns `true` to stop; the return value says whether the search
+    /// stopped early (solution accepted or node budget spent), in which case the board is left as it was.
+    pub(crate) fn backtrack(&mut self, on_solution: &mut dyn FnMut(&GameGrid) -> bool) -> bool {
+        if self.visit_node() { return true; }
         // If all cells filled, verify all regions satisfied
         if self.occupied.len() == self.rule_index.len() {
-            return self
-                .parsed_rules
-                .iter()
-                .enumerate()
-                .all(|(i, _)| matches!(self.region_state(i), RegionState::Satisfied));
+            return self.all_regions_satisfied() && on_solution(self);
         }
         // Choose an empty coordinate (simple heuristic: first)
         let next_coord = *self
@@ -283,41 +808,46 @@ impl GameGrid {
             .find(|c| !self.occupied.contains_key(c))
             .unwrap();
         // Try to pair with an adjacent empty coord
-        let mut partner_candidates: Vec<Coord> = Self::neighbors(next_coord)
-            .filter(|c| self.rule_index.contains_key(c) && !self.occupied.contains_key(c))
-            .collect();
-        partner_candidates.sort_unstable(); // deterministic partner order
+        let partner_candidates: Vec<Coord> = self.neighbors(next_coord).iter()
+            .copied()
+            .filter(|c| !self.occupied.contains_key(c))
+            .collect(); // already sorted: deterministic partner order
         if partner_candidates.is_empty() {
             return false;
         }
+        let choices: usize = (0..self.domino_inventory.len()).filter(|&i| !self.domino_used[i])
+            .map(|i| if self.domino_inventory[i].0 == self.domino_inventory[i].1 { 1 } else { 2 })
+            .sum::<usize>() * partner_candidates.len();
+        let depth = self.branches.len();
+        let skip = self.resume.get(depth).copied().unwrap_or(0);
+        self.branches.push(Branch { index: 0, count: choices });
         // Domino inventory iteration
         for i in 0..self.domino_inventory.len() {
-            let domino = self.domino_inventory[i];
-            if domino == (255, 255) {
+            if self.domino_used[i] {
                 continue;
-            } // sentinel used when consumed
+            }
+            let domino = self.domino_inventory[i];
             for &partner in &partner_candidates {
                 let orientations: &[(u8,u8)] = if domino.0 == domino.1 { &[(domino.0, domino.1)] } else { &[(domino.0, domino.1), (domino.1, domino.0)] };
                 for &(a_val,b_val) in orientations {
-                    self.occupied.insert(next_coord, a_val);
-                    self.occupied.insert(partner, b_val);
-                    self.domino_ids.insert(next_coord, i);
-                    self.domino_ids.insert(partner, i);
-                    if self.affected_regions_feasible(&[next_coord, partner]) {
-                        let saved = domino; self.domino_inventory[i] = (255,255);
-                        if self.backtrack() { return true; }
-                        self.domino_inventory[i] = saved;
-                    }
-                    self.occupied.remove(&next_coord);
-                    self.occupied.remove(&partner);
-                    self.domino_ids.remove(&next_coord);
-                    self.domino_ids.remove(&partner);
+                    if self.branches[depth].index < skip { self.branches[depth].index += 1; continue; }
+                    self.place(i, next_coord, partner, a_val, b_val);
+                    if self.affected_regions_feasible(&[next_coord, partner]) && self.backtrack(on_solution) { return true; }
+                    self.unplace(next_coord, partner);
+                    self.branches[depth].index += 1;
                 }
             }
         }
+        self.branches.pop();
         false
     }
 
+    /// Inventory index of the domino covering `c` in the current assignment.
+    pub(crate) fn domino_at(&self, c: Coord) -> Option<usize> { self.domino_ids.get(&c).copied() }
+
+    /// Characters per cell in the ASCII renderers: two-digit pip values get two columns on every cell.
+    fn cell_width(&self) -> usize { self.max_pip.to_string().len() }
+
     /// Render the current grid as ASCII with origin at bottom-left (y increases upward).
     /// Each occupied cell shows its pip value; undefined coordinates are blank.
     pub fn ascii_board_bottom_origin(&self) -> String {
@@ -326,13 +856,15 @@ impl GameGrid {
         for &(x,y) in self.rule_index.keys() { min_x = min_x.min(x); min_y = min_y.min(y); max_x = max_x.max(x); max_y = max_y.max(y); }
         use std::fmt::Write;
         let mut out = String::new();
+        let w = self.cell_width();
         for y in (min_y..=max_y).rev() { // top to bottom so origin visually bottom-left
+            if self.topology.row_shifted(y) { out.push_str(&" ".repeat(w.div_ceil(2))); }
             for x in min_x..=max_x {
                 let c = (x,y);
                 if self.rule_index.contains_key(&c) {
-                    if let Some(v) = self.occupied.get(&c) { write!(out, "{v} ").ok(); } else { out.push_str(". "); }
+                    if let Some(v) = self.occupied.get(&c) { write!(out, "{v:>w$} ").ok(); } else { write!(out, "{:>w$} ", ".").ok(); }
                 } else {
-                    out.push_str("  ");
+                    write!(out, "{:w$} ", "").ok();
                 }
             }
             out.push('\n');
@@ -346,15 +878,17 @@ impl GameGrid {
         let mut min_x = u32::MAX; let mut min_y = u32::MAX; let mut max_x = 0u32; let mut max_y = 0u32;
         for &(x,y) in self.rule_index.keys() { min_x = min_x.min(x); min_y = min_y.min(y); max_x = max_x.max(x); max_y = max_y.max(y); }
         use std::fmt::Write; let mut out = String::new();
+        let w = self.cell_width();
         for y in (min_y..=max_y).rev() {
+            if self.topology.row_shifted(y) { out.push_str(&" ".repeat(w.div_ceil(2))); }
             for x in min_x..=max_x {
                 let c=(x,y);
                 if self.rule_index.contains_key(&c) {
                     if let Some(&v)=self.occupied.get(&c) {
                         let id = self.domino_ids.get(&c).copied();
-                        if let Some(idx) = id { let (start,end)=color_for_domino(idx); write!(out, "{start}{v}{end} ").ok(); } else { write!(out, "{v} ").ok(); }
-                    } else { out.push_str(". "); }
-                } else { out.push_str("  "); }
+                        if let Some(idx) = id { let (start,end)=color_for_domino(idx); write!(out, "{start}{v:>w$}{end} ").ok(); } else { write!(out, "{v:>w$} ").ok(); }
+                    } else { write!(out, "{:>w$} ", ".").ok(); }
+                } else { write!(out, "{:w$} ", "").ok(); }
             }
             out.push('\n');
         }
@@ -384,31 +918,244 @@ fn color_for_domino(idx: usize) -> (&'static str, &'static str) {
     (PAL[idx % PAL.len()], RESET)
 }
 
+/// Puzzles shared by the unit tests of every module.
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::GridFile;
+
+    /// Directory of the puzzle fixtures.
+    pub(crate) const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/grids");
+
+    /// Path of the fixture `name` (JSON or text format).
+    pub(crate) fn fixture_path(name: &str) -> String { format!("{FIXTURES}/{name}") }
+
+    /// Load the fixture `name`.
+    pub(crate) fn fixture(name: &str) -> GridFile { GridFile::from_path(&fixture_path(name)).unwrap() }
+
+    /// 4x4 unconstrained board with eight distinct dominoes: far too many solutions to enumerate in a test.
+    pub(crate) fn open_board() -> GridFile { fixture("slow/open_board.json") }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::test_support::{fixture, fixture_path};
+
+    fn rule(s: &str) -> Rule { Rule::parse(s, NotEqualMode::default()) }
 
     #[test]
     fn rule_parse_basic() {
-        assert!(matches!(Rule::parse("="), Rule::Equal));
-    assert!(matches!(Rule::parse("!="), Rule::NotEqual));
-        assert!(matches!(Rule::parse("x"), Rule::Any));
-        assert!(matches!(Rule::parse(">3"), Rule::GreaterThan(3)));
-        assert!(matches!(Rule::parse("<7"), Rule::LessThan(7)));
-        assert!(matches!(Rule::parse("10"), Rule::Sum(10)));
+        assert!(matches!(rule("="), Rule::Equal));
+        assert!(matches!(rule("!="), Rule::AllDifferent));
+        assert!(matches!(Rule::parse("!=", NotEqualMode::NotAllEqual), Rule::NotEqual));
+        assert!(matches!(rule("x"), Rule::Any));
+        assert!(matches!(rule(">3"), Rule::GreaterThan(3)));
+        assert!(matches!(rule("<7"), Rule::LessThan(7)));
+        assert!(matches!(rule("10"), Rule::Sum(10)));
+    }
+
+    #[test]
+    fn rule_parse_combinators() {
+        assert_eq!(rule("3..7"), Rule::Range(3, 7));
+        assert_eq!(rule("7..3"), Rule::Unknown);
+        assert_eq!(rule("odd"), Rule::Odd);
+        assert_eq!(rule("even"), Rule::Even);
+        assert_eq!(rule("distinct"), Rule::AllDifferent);
+        assert_eq!(rule("{1, 3,5}"), Rule::OneOf(0b101010));
+        assert_eq!(rule("{1,x}"), Rule::Unknown);
+    }
+
+    #[test]
+    fn rule_parse_alternative_spellings() {
+        for (spelling, canonical) in [
+            (" = ", "="), ("equal", "="), ("Equals", "="), ("≠", "!="), ("unequal", "!="), ("any", "x"), ("Empty", "x"),
+            ("∑=5", "5"), ("Σ5", "5"), ("sum:5", "5"), ("sum = 5", "5"), ("sum:3 .. 7", "3..7"), ("< 4", "<4"),
+            ("{ 5,1 ,3 }", "{1,3,5}"), ("ODD", "odd"), ("all-different", "!="),
+        ] {
+            assert_eq!(rule(spelling).to_string(), canonical, "{spelling:?}");
+        }
+        assert_eq!(rule("sum:odd"), Rule::Unknown);
+        assert_eq!(canonical_rule("??", NotEqualMode::default()), "??", "unknown rules are kept as written");
+        assert_eq!(canonical_rule("≠", NotEqualMode::NotAllEqual), "!=");
+        assert_eq!(canonical_rule("distinct", NotEqualMode::NotAllEqual), "distinct");
+    }
+
+    #[test]
+    fn rule_tokens_round_trip() {
+        let rules = [Rule::Equal, Rule::NotEqual, Rule::AllDifferent, Rule::Sum(0), Rule::Sum(12), Rule::GreaterThan(3),
+            Rule::LessThan(9), Rule::Range(2, 4), Rule::Odd, Rule::Even, Rule::OneOf(0b1001), Rule::Any];
+        for mode in [NotEqualMode::AllDifferent, NotEqualMode::NotAllEqual] {
+            for r in rules {
+                assert_eq!(Rule::parse(&r.token(mode), mode), r, "{r:?} in {mode:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn json_rules_as_numbers_and_objects() {
+        let parsed: GridFile = serde_json::from_str(r#"{"grid": [
+            {"rule": 5, "coords": [[1,1]]},
+            {"rule": {"type": "less", "target": 3}, "coords": [[2,1]]},
+            {"rule": {"type": "equals"}, "coords": [[3,1]]}
+        ], "dominoes": []}"#).unwrap();
+        let rules: Vec<&str> = parsed.grid.iter().map(|e| e.rule.as_str()).collect();
+        assert_eq!(rules, ["5", "<3", "="]);
+        let err = serde_json::from_str::<GridFile>(r#"{"grid": [{"rule": {"type": "sum"}, "coords": []}], "dominoes": []}"#);
+        assert!(err.unwrap_err().to_string().contains("needs a target"));
+        assert!(serde_json::from_str::<GridFile>(r#"{"grid": [{"rule": true, "coords": []}], "dominoes": []}"#).is_err());
+    }
+
+    #[test]
+    fn max_pip_bounds_validation_and_rendering() {
+        let mut g = GameGrid::from_file(&fixture_path("double_twelve_grid.json")).unwrap();
+        assert!(g.solve().is_some());
+        let board = g.ascii_board(false);
+        assert!(board.lines().all(|l| l.len() == 6), "two columns per cell plus a space:\n{board}");
+        assert!(board.contains("10 10"));
+        // A sum of 20 over two cells is out of reach for a double-six set but not for double-nine.
+        let mut file = GridFile { grid: vec![GridEntry { rule: ">19".into(), coords: vec![(1, 1), (2, 1), (3, 1)], ..Default::default() }], dominoes: vec![], ..Default::default() };
+        file.grid.push(GridEntry { rule: "x".into(), coords: vec![(4, 1)], ..Default::default() });
+        let mut g = GameGrid::from_parsed(file.clone());
+        g.occupied.insert((1, 1), 6);
+        assert_eq!(g.region_state(0), RegionState::Violated);
+        let mut g9 = GameGrid::from_parsed(GridFile { max_pip: Some(9), ..file.clone() });
+        g9.occupied.insert((1, 1), 6);
+        assert_eq!(g9.region_state(0), RegionState::Incomplete);
+        assert!(GridFile { dominoes: vec![(7, 1)], ..file.clone() }.validate().unwrap_err().contains("exceeds"));
+        assert!(GridFile { dominoes: vec![(7, 1)], max_pip: Some(9), ..file.clone() }.validate().is_ok());
+        assert!(GridFile { max_pip: Some(40), ..file }.validate().is_err());
+        // Unvalidated inventories above the declared maximum still prune soundly.
+        let over: GridFile = serde_json::from_str(r#"{"grid": [{"rule": "17", "coords": [[1,1],[2,1]]}], "dominoes": [[9,8]]}"#).unwrap();
+        let mut g = GameGrid::from_parsed(over.clone());
+        assert_eq!(g.max_pip, 9);
+        assert!(g.solve().is_some());
+        crate::dimacs::encode(&GameGrid::from_parsed(over));
+    }
+
+    #[test]
+    fn topology_drives_adjacency() {
+        for name in ["hex_grid.json", "torus_grid.json"] {
+            let file = fixture(name);
+            let mut g = GameGrid::from_parsed(file.clone());
+            let pips = g.solve().unwrap_or_else(|| panic!("{name} should solve"));
+            assert!(g.check_assignment(&pips));
+            let mut placements = g.placements();
+            assert!(GameGrid::from_parsed(GridFile { topology: Topology::Square, ..file.clone() }).solve().is_none(),
+                "{name} needs its topology");
+            let mut reloaded = GameGrid::from_parsed(file);
+            reloaded.apply_placements(&placements).unwrap();
+            placements[0].cells.swap(0, 1);
+            placements[0].pips.swap(0, 1);
+            reloaded.apply_placements(&placements).unwrap();
+        }
+        // Explicit adjacency, given in the declared convention: only (0,0)-(2,0) may hold a domino.
+        let explicit: GridFile = serde_json::from_str(r#"{"coordinates": {"base": 0}, "topology": {"kind": "explicit", "edges": [[[0,0],[2,0]]]},
+            "grid": [{"rule": "x", "coords": [[0,0],[2,0]]}], "dominoes": [[1,2]]}"#).unwrap();
+        explicit.validate().unwrap();
+        let mut g = GameGrid::from_parsed(explicit.clone());
+        assert_eq!(g.neighbors((1, 1)), &[(3, 1)]);
+        assert!(g.solve().is_some());
+        let dangling = GridFile { topology: Topology::Explicit { edges: vec![[(0, 0), (5, 5)]] }, ..explicit };
+        assert!(dangling.validate().unwrap_err().contains("not on the board"));
+        // Hex rows count from the declared origin: with a top-left origin the top row is shifted, however tall the board.
+        for rows in [2, 3, 4] {
+            let cells: Vec<String> = (0..rows).flat_map(|r| (0..2).map(move |c| format!("[{r},{c}]"))).collect();
+            let hex: GridFile = serde_json::from_str(&format!(r#"{{"coordinates": {{"origin": "top-left", "base": 0, "axes": "row-col"}},
+                "topology": {{"kind": "hex"}}, "grid": [{{"rule": "x", "coords": [{}]}}], "dominoes": []}}"#, cells.join(","))).unwrap();
+            let g = GameGrid::from_parsed(hex);
+            let (top, second) = (g.to_internal((0, 0)), g.to_internal((1, 0)));
+            assert!(g.topology.row_shifted(top.1) && !g.topology.row_shifted(second.1), "{rows} rows");
+            assert!(g.neighbors(top).contains(&g.to_internal((1, 1))), "{rows} rows: the top row leans right");
+        }
+    }
+
+    #[test]
+    fn hex_rows_are_indented() {
+        let mut g = GameGrid::from_parsed(fixture("hex_grid.json"));
+        g.solve().unwrap();
+        let board = g.ascii_board(false);
+        let lines: Vec<&str> = board.lines().collect();
+        assert!(lines[0].starts_with("  ") && lines[1].starts_with(' ') && !lines[1].starts_with("  "), "{board}");
+    }
+
+    #[test]
+    fn shared_cells_and_cells_without_region() {
+        // (2,1) is in both regions; (3,1) and (4,1) belong to none but must still be covered.
+        let file: GridFile = serde_json::from_str(r#"{"grid": [
+            {"rule": "=", "coords": [[1,1],[2,1]]},
+            {"rule": "odd", "coords": [[2,1]]}
+        ], "cells": [[3,1],[4,1]], "dominoes": [[2,2],[3,3],[1,6]]}"#).unwrap();
+        let mut g = GameGrid::from_parsed(file.clone());
+        assert_eq!(g.rule_index[&(2, 1)], vec!["=".to_string(), "odd".to_string()]);
+        assert!(g.rule_index[&(3, 1)].is_empty());
+        let pips = g.solve().expect("3-3 fits both regions");
+        assert_eq!((pips[&(1, 1)], pips[&(2, 1)]), (3, 3));
+        assert_eq!(pips.len(), 4);
+        assert!(GameGrid::from_parsed(GridFile { cells: vec![(3, 1)], ..file.clone() }).solve().is_none(), "three cells cannot be tiled");
+
+        assert_eq!(file.overlap_warnings().len(), 1);
+        assert!(file.overlap_warnings()[0].contains("share cell (2,1)"));
+        let mut marked = file.clone();
+        marked.grid[1].overlap = true;
+        assert!(marked.overlap_warnings().is_empty());
+        let json = crate::json_format::write(&marked);
+        assert!(json.contains("\"overlap\": true") && json.contains("\"cells\": [[3, 1], [4, 1]]"), "{json}");
+        assert_eq!(serde_json::from_str::<GridFile>(&json).unwrap(), crate::json_format::canonical(&marked));
+    }
+
+    #[test]
+    fn read_grid_files_single_and_lines() {
+        let single = "{\n  \"grid\": [{\"rule\": \"x\", \"coords\": [[1,1],[2,1]]}],\n  \"dominoes\": [[1,2]]\n}\n";
+        assert_eq!(read_grid_files(single.as_bytes()).count(), 1);
+        let lines = "{\"grid\":[],\"dominoes\":[]}\n\n{\"grid\":[{\"rule\":\"=\",\"coords\":[[1,1]]}],\"dominoes\":[]}\n";
+        let parsed: Vec<GridFile> = read_grid_files(lines.as_bytes()).collect::<Result<_, _>>().unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[1].grid[0].rule, "=");
+        assert!(read_grid_files("{\"grid\": oops}".as_bytes()).next().unwrap().is_err());
+        // A bad record in the middle reports its line and the stream carries on.
+        let bad = "{\"grid\":[],\"dominoes\":[]}\n{\"grid\": oops, \"dominoes\": []}\n{\"grid\":[],\"dominoes\":[[1,2]]}\n";
+        let results: Vec<Result<GridFile, String>> = read_grid_files(bad.as_bytes()).collect();
+        assert_eq!(results.len(), 3);
+        assert!(matches!(&results[1], Err(e) if e.starts_with("line 2: ")), "{results:?}");
+        assert_eq!(results[2].as_ref().unwrap().dominoes, vec![(1, 2)]);
+        // Two documents on one line, and a pretty-printed one cut short.
+        assert_eq!(read_grid_files("{\"grid\":[],\"dominoes\":[]} {\"grid\":[],\"dominoes\":[]}".as_bytes()).count(), 2);
+        let cut: Vec<_> = read_grid_files("{\n  \"grid\": [],\n".as_bytes()).collect();
+        assert!(matches!(&cut[..], [Err(e)] if e.starts_with("line 1: ")), "{cut:?}");
+    }
+
+    #[test]
+    fn declared_coordinates_are_normalised() {
+        // Same two-row board written top-left, 0-based, [row, col]: row 0 holds the "=" pair.
+        let json = r#"{"coordinates": {"origin": "top-left", "base": 0, "axes": "row-col"},
+            "grid": [{"rule": "=", "coords": [[0,0],[0,1]]}, {"rule": "7", "coords": [[1,0],[1,1]]}],
+            "dominoes": [[3,4],[2,2]]}"#;
+        let parsed: GridFile = serde_json::from_str(json).unwrap();
+        parsed.validate().unwrap();
+        let mut g = GameGrid::from_parsed(parsed.clone());
+        assert_eq!(g.ordered_coords, vec![(1,1),(1,2),(2,1),(2,2)]);
+        let sol = g.solve().unwrap();
+        assert_eq!(sol[&(0,0)], 2);
+        assert_eq!(sol[&(0,1)], 2);
+        assert_eq!(sol[&(1,0)] + sol[&(1,1)], 7);
+        assert!(g.check_assignment(&sol));
+        assert_eq!(g.ascii_board(false).lines().next().unwrap().trim_end(), "2 2");
+        let bad: GridFile = serde_json::from_str(r#"{"coordinates": {"origin": "top-left"}, "grid": [{"rule": "x", "coords": [[0,1]]}], "dominoes": []}"#).unwrap();
+        assert!(bad.validate().is_err());
     }
 
     #[test]
     fn rule_parse_unknown() {
-        assert!(matches!(Rule::parse("??"), Rule::Unknown));
+        assert!(matches!(rule("??"), Rule::Unknown));
     }
 
     #[test]
     fn solve_trivial_two_cells() {
         // Two adjacent cells with a single domino (2,5) and no constraints other than presence.
         let parsed = GridFile {
-            grid: vec![GridEntry { rule: "x".to_string(), coords: vec![(0,0),(1,0)] }],
+            grid: vec![GridEntry { rule: "x".to_string(), coords: vec![(0,0),(1,0)], ..Default::default() }],
             dominoes: vec![(2,5)],
+            ..Default::default()
         };
         let mut g = GameGrid::from_parsed(parsed);
         let sol = g.solve().expect("should solve");
@@ -423,8 +1170,9 @@ mod tests {
     #[test]
     fn ascii_color_flag_changes_output() {
         let parsed = GridFile {
-            grid: vec![GridEntry { rule: "x".into(), coords: vec![(0,0),(1,0)] }],
+            grid: vec![GridEntry { rule: "x".into(), coords: vec![(0,0),(1,0)], ..Default::default() }],
             dominoes: vec![(1,1)],
+            ..Default::default()
         };
         let mut g = GameGrid::from_parsed(parsed);
         g.solve().unwrap();
@@ -436,7 +1184,7 @@ mod tests {
 
     #[test]
     fn ascii_empty_grid() {
-        let parsed = GridFile { grid: vec![], dominoes: vec![] };
+        let parsed = GridFile { grid: vec![], dominoes: vec![], ..Default::default() };
         let g = GameGrid::from_parsed(parsed);
         assert_eq!(g.ascii_board(false), "");
         assert_eq!(g.ascii_board(true), "");
@@ -445,7 +1193,7 @@ mod tests {
     #[test]
     fn unsolvable_two_cells_equal_rule() {
         // Rule requires equality but only domino (1,2) available.
-        let parsed = GridFile { grid: vec![GridEntry{ rule: "=".into(), coords: vec![(0,0),(1,0)] }], dominoes: vec![(1,2)] };
+        let parsed = GridFile { grid: vec![GridEntry{ rule: "=".into(), coords: vec![(0,0),(1,0)], ..Default::default() }], dominoes: vec![(1,2)], ..Default::default() };
         let mut g = GameGrid::from_parsed(parsed);
         assert!(g.solve().is_none());
     }
@@ -453,7 +1201,7 @@ mod tests {
     // Region state branch coverage tests
     #[test]
     fn region_equal_violated() {
-        let parsed = GridFile { grid: vec![GridEntry{ rule: "=".into(), coords: vec![(0,0),(1,0)] }], dominoes: vec![(1,1)] };
+        let parsed = GridFile { grid: vec![GridEntry{ rule: "=".into(), coords: vec![(0,0),(1,0)], ..Default::default() }], dominoes: vec![(1,1)], ..Default::default() };
         let mut g = GameGrid::from_parsed(parsed);
         g.occupied.insert((0,0), 1);
         g.occupied.insert((1,0), 2);
@@ -463,17 +1211,17 @@ mod tests {
     #[test]
     fn region_sum_variants() {
         // sum > target
-        let parsed = GridFile { grid: vec![GridEntry{ rule: "3".into(), coords: vec![(0,0),(1,0)] }], dominoes: vec![] };
+        let parsed = GridFile { grid: vec![GridEntry{ rule: "3".into(), coords: vec![(0,0),(1,0)], ..Default::default() }], dominoes: vec![], ..Default::default() };
         let mut g = GameGrid::from_parsed(parsed);
         g.occupied.insert((0,0),2); g.occupied.insert((1,0),2);
         assert!(matches!(g.region_state(0), RegionState::Violated));
         // max_possible < target
-        let parsed2 = GridFile { grid: vec![GridEntry{ rule: "8".into(), coords: vec![(0,0),(1,0)] }], dominoes: vec![] };
+        let parsed2 = GridFile { grid: vec![GridEntry{ rule: "8".into(), coords: vec![(0,0),(1,0)], ..Default::default() }], dominoes: vec![], ..Default::default() };
         let mut g2 = GameGrid::from_parsed(parsed2);
         g2.occupied.insert((0,0),1); // one empty cell left => max_possible 7 <8
         assert!(matches!(g2.region_state(0), RegionState::Violated));
         // satisfied final
-        let parsed3 = GridFile { grid: vec![GridEntry{ rule: "5".into(), coords: vec![(0,0),(1,0)] }], dominoes: vec![] };
+        let parsed3 = GridFile { grid: vec![GridEntry{ rule: "5".into(), coords: vec![(0,0),(1,0)], ..Default::default() }], dominoes: vec![], ..Default::default() };
         let mut g3 = GameGrid::from_parsed(parsed3);
         g3.occupied.insert((0,0),2); g3.occupied.insert((1,0),3);
         assert!(matches!(g3.region_state(0), RegionState::Satisfied));
@@ -481,31 +1229,76 @@ mod tests {
 
     #[test]
     fn region_not_equal_variants() {
+        // Legacy "not all equal" meaning of `!=`.
         // Incomplete with only one value placed
-        let parsed = GridFile { grid: vec![GridEntry{ rule: "!=".into(), coords: vec![(0,0),(1,0)] }], dominoes: vec![] };
+        let parsed = GridFile { grid: vec![GridEntry{ rule: "!=".into(), coords: vec![(0,0),(1,0)], ..Default::default() }], dominoes: vec![], not_equal: NotEqualMode::NotAllEqual, ..Default::default() };
         let mut g = GameGrid::from_parsed(parsed);
         g.occupied.insert((0,0), 2);
         assert!(matches!(g.region_state(0), RegionState::Incomplete));
         // Satisfied when two different values present (even with empties left it stays satisfied)
-        let parsed2 = GridFile { grid: vec![GridEntry{ rule: "!=".into(), coords: vec![(0,0),(1,0),(2,0)] }], dominoes: vec![] };
+        let parsed2 = GridFile { grid: vec![GridEntry{ rule: "!=".into(), coords: vec![(0,0),(1,0),(2,0)], ..Default::default() }], dominoes: vec![], not_equal: NotEqualMode::NotAllEqual, ..Default::default() };
         let mut g2 = GameGrid::from_parsed(parsed2);
         g2.occupied.insert((0,0), 3); g2.occupied.insert((1,0), 4);
         assert!(matches!(g2.region_state(0), RegionState::Satisfied));
         // Violated if fully filled and all equal
-        let parsed3 = GridFile { grid: vec![GridEntry{ rule: "!=".into(), coords: vec![(0,0),(1,0)] }], dominoes: vec![] };
+        let parsed3 = GridFile { grid: vec![GridEntry{ rule: "!=".into(), coords: vec![(0,0),(1,0)], ..Default::default() }], dominoes: vec![], not_equal: NotEqualMode::NotAllEqual, ..Default::default() };
         let mut g3 = GameGrid::from_parsed(parsed3);
         g3.occupied.insert((0,0),5); g3.occupied.insert((1,0),5);
         assert!(matches!(g3.region_state(0), RegionState::Violated));
         // Degenerate single-cell region: cannot satisfy once filled
-        let parsed4 = GridFile { grid: vec![GridEntry{ rule: "!=".into(), coords: vec![(0,0)] }], dominoes: vec![] };
+        let parsed4 = GridFile { grid: vec![GridEntry{ rule: "!=".into(), coords: vec![(0,0)], ..Default::default() }], dominoes: vec![], not_equal: NotEqualMode::NotAllEqual, ..Default::default() };
         let mut g4 = GameGrid::from_parsed(parsed4);
         g4.occupied.insert((0,0),1);
         assert!(matches!(g4.region_state(0), RegionState::Violated));
     }
 
+    #[test]
+    fn not_equal_defaults_to_all_different() {
+        // Two different values are not enough while a third cell may still repeat one of them.
+        assert_eq!(state_of("!=", &[3, 4]), RegionState::Incomplete);
+        assert_eq!(state_of("!=", &[3, 4, 3]), RegionState::Violated);
+        assert_eq!(state_of("!=", &[3, 3]), RegionState::Violated);
+        assert_eq!(state_of("!=", &[3, 4, 5]), RegionState::Satisfied);
+        let legacy: GridFile = serde_json::from_str(r#"{"not_equal": "not-all-equal",
+            "grid": [{"rule": "!=", "coords": [[1,1],[2,1],[3,1]]}, {"rule": "x", "coords": [[4,1]]}], "dominoes": [[1,1],[2,2]]}"#).unwrap();
+        assert!(GameGrid::from_parsed(legacy.clone()).solve().is_some(), "1-1 next to 2 is not all equal");
+        assert!(GameGrid::from_parsed(GridFile { not_equal: NotEqualMode::default(), ..legacy }).solve().is_none());
+    }
+
+    /// Region state of a single region `rule` over three cells with the given values filled in.
+    fn state_of(rule: &str, values: &[u8]) -> RegionState {
+        let parsed = GridFile { grid: vec![GridEntry{ rule: rule.into(), coords: vec![(1,1),(2,1),(3,1)], ..Default::default() }], dominoes: vec![], ..Default::default() };
+        let mut g = GameGrid::from_parsed(parsed);
+        for (x, &v) in values.iter().enumerate() { g.occupied.insert((x as u32 + 1, 1), v); }
+        g.region_state(0)
+    }
+
+    #[test]
+    fn region_combinator_variants() {
+        use RegionState::*;
+        assert_eq!(state_of("3..7", &[1]), Incomplete);
+        assert_eq!(state_of("3..7", &[6, 2]), Violated); // already above 7
+        assert_eq!(state_of("15..18", &[1]), Violated); // 1 + 6 + 6 cannot reach 15
+        assert_eq!(state_of("3..7", &[1, 1, 1]), Satisfied);
+        assert_eq!(state_of("3..7", &[0, 1, 1]), Violated);
+        assert_eq!(state_of("odd", &[1, 2]), Incomplete);
+        assert_eq!(state_of("odd", &[1, 2, 2]), Satisfied);
+        assert_eq!(state_of("even", &[1, 2, 2]), Violated);
+        assert_eq!(state_of("distinct", &[4, 4]), Violated); // pruned before the region is full
+        assert_eq!(state_of("distinct", &[4, 0]), Incomplete);
+        assert_eq!(state_of("distinct", &[4, 0, 6]), Satisfied);
+        assert_eq!(state_of("{1,3,5}", &[3, 2]), Violated);
+        assert_eq!(state_of("{1,3,5}", &[3, 5]), Incomplete);
+        assert_eq!(state_of("{1,3,5}", &[3, 5, 3]), Satisfied);
+        // Pips past the mask's width (only reachable by bypassing validation) are out of the set, not a panic.
+        assert_eq!(state_of("{1,3,5}", &[40]), Violated);
+        assert_eq!(state_of("distinct", &[40, 200, 40]), Violated);
+        assert_eq!(state_of("distinct", &[40, 200]), Incomplete);
+    }
+
     #[test]
     fn solve_not_equal_example() {
-        let parsed = GridFile { grid: vec![GridEntry{ rule: "!=".into(), coords: vec![(0,0),(1,0)] }], dominoes: vec![(1,2)] };
+        let parsed = GridFile { grid: vec![GridEntry{ rule: "!=".into(), coords: vec![(0,0),(1,0)], ..Default::default() }], dominoes: vec![(1,2)], ..Default::default() };
         let mut g = GameGrid::from_parsed(parsed);
         let sol = g.solve().expect("should solve");
         let a = sol.get(&(0,0)).unwrap();
@@ -517,9 +1310,9 @@ mod tests {
     fn deterministic_solve() {
         // A slightly larger grid to exercise ordering.
         let parsed = GridFile { grid: vec![
-            GridEntry{ rule: "x".into(), coords: vec![(0,0),(1,0)] },
-            GridEntry{ rule: "!=".into(), coords: vec![(0,1),(1,1)] },
-        ], dominoes: vec![(1,2),(2,2)] };
+            GridEntry{ rule: "x".into(), coords: vec![(0,0),(1,0)], ..Default::default() },
+            GridEntry{ rule: "!=".into(), coords: vec![(0,1),(1,1)], ..Default::default() },
+        ], dominoes: vec![(1,2),(2,2)], ..Default::default() };
         let mut g1 = GameGrid::from_parsed(parsed.clone());
         let mut g2 = GameGrid::from_parsed(parsed);
         let s1 = g1.solve().unwrap();
@@ -530,17 +1323,17 @@ mod tests {
     #[test]
     fn region_greater_than_variants() {
         // satisfied
-        let parsed = GridFile { grid: vec![GridEntry{ rule: ">3".into(), coords: vec![(0,0),(1,0)] }], dominoes: vec![] };
+        let parsed = GridFile { grid: vec![GridEntry{ rule: ">3".into(), coords: vec![(0,0),(1,0)], ..Default::default() }], dominoes: vec![], ..Default::default() };
         let mut g = GameGrid::from_parsed(parsed);
         g.occupied.insert((0,0),2); g.occupied.insert((1,0),2);
         assert!(matches!(g.region_state(0), RegionState::Satisfied));
         // boundary violated final (sum == k)
-        let parsed2 = GridFile { grid: vec![GridEntry{ rule: ">3".into(), coords: vec![(0,0),(1,0)] }], dominoes: vec![] };
+        let parsed2 = GridFile { grid: vec![GridEntry{ rule: ">3".into(), coords: vec![(0,0),(1,0)], ..Default::default() }], dominoes: vec![], ..Default::default() };
         let mut g2 = GameGrid::from_parsed(parsed2);
         g2.occupied.insert((0,0),1); g2.occupied.insert((1,0),2);
         assert!(matches!(g2.region_state(0), RegionState::Violated));
         // max_possible <= k early violation
-        let parsed3 = GridFile { grid: vec![GridEntry{ rule: ">8".into(), coords: vec![(0,0),(1,0)] }], dominoes: vec![] };
+        let parsed3 = GridFile { grid: vec![GridEntry{ rule: ">8".into(), coords: vec![(0,0),(1,0)], ..Default::default() }], dominoes: vec![], ..Default::default() };
         let mut g3 = GameGrid::from_parsed(parsed3);
         g3.occupied.insert((0,0),2); // max possible 8
         assert!(matches!(g3.region_state(0), RegionState::Violated));
@@ -549,12 +1342,12 @@ mod tests {
     #[test]
     fn region_less_than_variants() {
         // satisfied final (sum < k)
-        let parsed = GridFile { grid: vec![GridEntry{ rule: "<5".into(), coords: vec![(0,0),(1,0)] }], dominoes: vec![] };
+        let parsed = GridFile { grid: vec![GridEntry{ rule: "<5".into(), coords: vec![(0,0),(1,0)], ..Default::default() }], dominoes: vec![], ..Default::default() };
         let mut g = GameGrid::from_parsed(parsed);
         g.occupied.insert((0,0),2); g.occupied.insert((1,0),2);
         assert!(matches!(g.region_state(0), RegionState::Satisfied));
         // violated sum >= k
-        let parsed2 = GridFile { grid: vec![GridEntry{ rule: "<4".into(), coords: vec![(0,0),(1,0)] }], dominoes: vec![] };
+        let parsed2 = GridFile { grid: vec![GridEntry{ rule: "<4".into(), coords: vec![(0,0),(1,0)], ..Default::default() }], dominoes: vec![], ..Default::default() };
         let mut g2 = GameGrid::from_parsed(parsed2);
         g2.occupied.insert((0,0),2); g2.occupied.insert((1,0),2);
         assert!(matches!(g2.region_state(0), RegionState::Violated));
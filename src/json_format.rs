@@ -0,0 +1,204 @@
+//! Canonical JSON writer for puzzles and the combined "puzzle + solution" document.
+//!
+//! The output keeps the hand-written fixture layout (one region per line, `[x, y]` pairs inline) so written files diff
+//! cleanly. A solved document is an ordinary puzzle file with an extra `solution` array, so the puzzle loader reads it
+//! unchanged:
+//! ```json
+//! {
+//!   "grid": [
+//!     { "rule": "=", "coords": [[1, 1], [2, 1]] }
+//!   ],
+//!   "dominoes": [[3, 3]],
+//!   "solution": [
+//!     { "cells": [[1, 1], [2, 1]], "pips": [3, 3] }
+//!   ]
+//! }
+//! ```
+
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::grid::{Coord, GridEntry, GridFile, Placement, canonical_rule};
+
+/// A puzzle together with a (possibly partial) solution.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SolvedFile {
+    #[serde(flatten)]
+    pub puzzle: GridFile,
+    #[serde(default)]
+    pub solution: Vec<Placement>,
+}
+
+/// Canonical form used by the writer: rule tokens in their canonical spelling, coordinates sorted within each region
+/// and regions ordered by their first coordinate (then rule). Domino order is kept, as it decides domino identity and colours.
+pub fn canonical(file: &GridFile) -> GridFile {
+    let mut grid: Vec<GridEntry> = file.grid.iter().map(|e| {
+        let mut coords = e.coords.clone();
+        coords.sort_unstable();
+        coords.dedup();
+        GridEntry { rule: canonical_rule(&e.rule, file.not_equal), coords, overlap: e.overlap }
+    }).collect();
+    grid.sort_by(|a, b| a.coords.first().cmp(&b.coords.first()).then_with(|| a.rule.cmp(&b.rule)));
+    let mut cells = file.cells.clone();
+    cells.sort_unstable();
+    cells.dedup();
+    GridFile { grid, cells, ..file.clone() }
+}
+
+/// Write a puzzle as canonical pretty-printed JSON.
+pub fn write(file: &GridFile) -> String {
+    write_document(&canonical(file), None)
+}
+
+/// Write a puzzle plus solution placements (cells in the puzzle's declared convention), sorted by cell.
+pub fn write_solved(file: &GridFile, solution: &[Placement]) -> String {
+    let mut placements = solution.to_vec();
+    placements.sort_unstable();
+    write_document(&canonical(file), Some(&placements))
+}
+
+/// Parse a puzzle document, with or without a `solution`.
+pub fn read_solved(json: &str) -> Result<SolvedFile, String> {
+    let solved: SolvedFile = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    solved.puzzle.validate()?;
+    Ok(solved)
+}
+
+fn pair(c: Coord) -> String { format!("[{}, {}]", c.0, c.1) }
+
+/// Single-line JSON with `", "` and `": "` separators, matching the rest of the layout.
+struct Inline;
+
+impl serde_json::ser::Formatter for Inline {
+    fn begin_array_value<W: ?Sized + std::io::Write>(&mut self, w: &mut W, first: bool) -> std::io::Result<()> {
+        if first { Ok(()) } else { w.write_all(b", ") }
+    }
+
+    fn begin_object_key<W: ?Sized + std::io::Write>(&mut self, w: &mut W, first: bool) -> std::io::Result<()> {
+        if first { Ok(()) } else { w.write_all(b", ") }
+    }
+
+    fn begin_object_value<W: ?Sized + std::io::Write>(&mut self, w: &mut W) -> std::io::Result<()> {
+        w.write_all(b": ")
+    }
+}
+
+fn inline<T: Serialize>(value: &T) -> String {
+    let mut buf = Vec::new();
+    value.serialize(&mut serde_json::Serializer::with_formatter(&mut buf, Inline)).ok();
+    String::from_utf8(buf).unwrap_or_default()
+}
+
+fn write_document(file: &GridFile, solution: Option<&[Placement]>) -> String {
+    let mut out = String::from("{\n");
+    if !file.coordinates.is_default() {
+        writeln!(out, "  \"coordinates\": {},", inline(&file.coordinates)).ok();
+    }
+    if let Some(max_pip) = file.max_pip {
+        writeln!(out, "  \"max_pip\": {max_pip},").ok();
+    }
+    if !file.topology.is_square() {
+        writeln!(out, "  \"topology\": {},", inline(&file.topology)).ok();
+    }
+    if !file.not_equal.is_default() {
+        writeln!(out, "  \"not_equal\": {},", serde_json::to_string(&file.not_equal).unwrap_or_default()).ok();
+    }
+    out.push_str("  \"grid\": [");
+    for (i, e) in file.grid.iter().enumerate() {
+        let coords: Vec<String> = e.coords.iter().map(|&c| pair(c)).collect();
+        let rule = serde_json::to_string(&e.rule).unwrap_or_default();
+        let overlap = if e.overlap { ", \"overlap\": true" } else { "" };
+        write!(out, "{}\n    {{ \"rule\": {rule}, \"coords\": [{}]{overlap} }}", if i == 0 { "" } else { "," }, coords.join(", ")).ok();
+    }
+    out.push_str(if file.grid.is_empty() { "],\n" } else { "\n  ],\n" });
+    if !file.cells.is_empty() {
+        let cells: Vec<String> = file.cells.iter().map(|&c| pair(c)).collect();
+        writeln!(out, "  \"cells\": [{}],", cells.join(", ")).ok();
+    }
+    let dominoes: Vec<String> = file.dominoes.iter().map(|&(a, b)| format!("[{a}, {b}]")).collect();
+    write!(out, "  \"dominoes\": [{}]", dominoes.join(", ")).ok();
+    if let Some(solution) = solution {
+        out.push_str(",\n  \"solution\": [");
+        for (i, p) in solution.iter().enumerate() {
+            write!(out, "{}\n    {{ \"cells\": [{}, {}], \"pips\": [{}, {}] }}", if i == 0 { "" } else { "," },
+                pair(p.cells[0]), pair(p.cells[1]), p.pips[0], p.pips[1]).ok();
+        }
+        out.push_str(if solution.is_empty() { "]" } else { "\n  ]" });
+    }
+    out.push_str("\n}\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{GameGrid, NotEqualMode};
+    use crate::grid::test_support::fixture;
+
+    #[test]
+    fn canonical_writer_is_stable_and_round_trips() {
+        for name in ["easy_grid.json", "medium_grid.json", "hard_grid.json", "easy_grid.pips", "double_twelve_grid.json", "hex_grid.json", "torus_grid.json"] {
+            let file = fixture(name);
+            let json = write(&file);
+            let back: GridFile = serde_json::from_str(&json).unwrap();
+            assert_eq!(back, canonical(&file), "{name}");
+            assert_eq!(write(&back), json, "writing canonical output again must not change it");
+        }
+        let mut shuffled = fixture("medium_grid.json");
+        shuffled.grid.reverse();
+        for e in &mut shuffled.grid { e.coords.reverse(); }
+        assert_eq!(write(&shuffled), write(&fixture("medium_grid.json")));
+    }
+
+    #[test]
+    fn easy_layout() {
+        let expected = "{\n  \"grid\": [\n    { \"rule\": \"4\", \"coords\": [[1, 1], [2, 1]] },\n    { \"rule\": \"=\", \"coords\": [[1, 2], [2, 2], [3, 2]] },\n    { \"rule\": \"x\", \"coords\": [[1, 3]] },\n    { \"rule\": \"1\", \"coords\": [[2, 3], [3, 3]] }\n  ],\n  \"dominoes\": [[0, 0], [3, 1], [3, 3], [4, 0]]\n}\n";
+        assert_eq!(write(&fixture("easy_grid.json")), expected);
+        assert!(write(&fixture("torus_grid.json")).contains("\"topology\": {\"kind\": \"torus\", \"width\": "));
+        // Separators inside strings are left alone.
+        assert_eq!(inline(&("a,b:c", [1, 2])), r#"["a,b:c", [1, 2]]"#);
+    }
+
+    #[test]
+    fn solved_document_round_trip() {
+        let file: GridFile = serde_json::from_str(r#"{"coordinates": {"origin": "top-left", "base": 0},
+            "grid": [{"rule": "=", "coords": [[0,0],[1,0]]}, {"rule": "7", "coords": [[0,1],[1,1]]}],
+            "dominoes": [[3,4],[2,2]]}"#).unwrap();
+        let mut g = GameGrid::from_parsed(file.clone());
+        let pips = g.solve().unwrap();
+        let doc = write_solved(&file, &g.placements());
+        assert!(doc.contains("\"coordinates\": {\"origin\": \"top-left\", \"base\": 0, \"axes\": \"xy\"}"));
+        // The plain loader ignores the solution; the solved reader restores it.
+        assert_eq!(canonical(&serde_json::from_str::<GridFile>(&doc).unwrap()), canonical(&file));
+        let solved = read_solved(&doc).unwrap();
+        let mut g2 = GameGrid::from_parsed(solved.puzzle);
+        g2.apply_placements(&solved.solution).unwrap();
+        assert!(g2.check_assignment(&pips));
+        assert_eq!(g2.ascii_board(true), g.ascii_board(true));
+        assert!(read_solved(&write(&file)).unwrap().solution.is_empty());
+    }
+
+    #[test]
+    fn legacy_not_equal_survives_rewrite() {
+        let mut file = fixture("not_equal_grid.json");
+        assert!(!write(&file).contains("not_equal"));
+        file.not_equal = NotEqualMode::NotAllEqual;
+        let json = write(&file);
+        assert!(json.starts_with("{\n  \"not_equal\": \"not-all-equal\",\n"), "{json}");
+        assert_eq!(serde_json::from_str::<GridFile>(&json).unwrap(), canonical(&file));
+    }
+
+    #[test]
+    fn apply_placements_rejects_bad_solutions() {
+        let file = fixture("easy_grid.json");
+        let mut g = GameGrid::from_parsed(file);
+        let far = Placement { cells: [(1, 1), (3, 3)], pips: [0, 0] };
+        assert!(g.apply_placements(&[far]).unwrap_err().contains("not adjacent"));
+        let missing = Placement { cells: [(1, 1), (2, 1)], pips: [6, 6] };
+        assert!(g.apply_placements(&[missing]).unwrap_err().contains("No unused domino"));
+        let twice = Placement { cells: [(1, 1), (2, 1)], pips: [0, 4] };
+        let again = Placement { cells: [(1, 2), (2, 2)], pips: [4, 0] };
+        assert!(g.apply_placements(&[twice, again]).is_err());
+    }
+}
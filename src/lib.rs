@@ -0,0 +1,29 @@
+//! Pips puzzle model, solvers and renderers.
+//!
+//! The command line tool in `main.rs` is a thin layer over this library. Everything here works on in-memory
+//! strings except `GridFile::from_path` / `GameGrid::from_file`, so the library also builds for
+//! `wasm32-unknown-unknown`; the `wasm` feature adds a JavaScript API on top (see `wasm`). Native builds also
+//! produce a shared library with a C API (see `ffi` and `include/pips_solver.h`).
+
+pub mod animate;
+pub mod canonical;
+pub mod checkpoint;
+pub mod coords;
+pub mod dimacs;
+pub mod dlx;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ffi;
+pub mod grid;
+pub mod json_format;
+pub mod nyt;
+pub mod progress;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod rating;
+pub mod solver;
+pub mod svg;
+pub mod text_format;
+pub mod topology;
+pub mod trace;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -1,6 +1,15 @@
-mod grid;
-use grid::GameGrid;
+mod batch;
+mod editor;
+mod serve;
+use batch::BatchFormat;
+use pips_solver::{animate, canonical, checkpoint, dimacs, grid, json_format, nyt, progress, solver, svg, text_format, trace};
+use grid::{GameGrid, GridFile};
+use solver::{SolveOptions, Solver};
 use std::env;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, IsTerminal, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 
 #[derive(Debug, PartialEq)]
 pub enum CliError {
@@ -9,14 +18,284 @@ pub enum CliError {
     WrongArity(usize),
     Other(String),
     Unsolvable,
+    /// Batch run finished but some puzzles failed to load (`errors`) or had no solution (`unsolvable`).
+    BatchFailed { errors: usize, unsolvable: usize },
 }
 
 /// Core CLI logic extracted for unit testing. Accepts the already-split argument list (no program name).
 pub fn run_cli(args: &[String]) -> Result<String, CliError> {
+    let mut out = Vec::new();
+    run_cli_to(args, &mut std::io::stdin().lock(), &mut out)?;
+    Ok(String::from_utf8_lossy(&out).into_owned())
+}
+
+/// Like `run_cli` but reads `-` from `input` and writes output as it is produced, so streamed results
+/// (multi-puzzle input, batch summaries) survive a later error.
+pub fn run_cli_to(args: &[String], input: &mut dyn BufRead, out: &mut dyn Write) -> Result<(), CliError> {
+    match args.first().map(String::as_str) {
+        Some("batch") => return run_batch(&args[1..], out),
+        Some("convert") => return run_convert(&args[1..], out),
+        Some("nyt") => return run_nyt(&args[1..], out),
+        Some("verify") => return run_verify(&args[1..], out),
+        Some("cnf") => return run_cnf(&args[1..], out),
+        Some("count") => return run_count(&args[1..], out),
+        Some("dedupe") => return run_dedupe(&args[1..], out),
+        Some("serve") => return run_serve(&args[1..], out),
+        Some("edit") => return run_edit(&args[1..], input, out),
+        Some("replay") => return run_replay(&args[1..], input, out),
+        _ => {}
+    }
     if args.is_empty() { return Err(CliError::Usage); }
+    let mut opts = SolveArgs { color: true, output: Output::Board, solver: solver::SOLVERS[0], trace: None, animate: None, delay_ms: None, progress: false };
+    let mut positional: Vec<String> = Vec::new();
+    let mut it = args.iter();
+    while let Some(a) = it.next() {
+        match a.as_str() {
+            "--no-color" | "--no-colors" | "-nc" => opts.color = false,
+            "--json" => opts.output = Output::Json,
+            "--svg" => opts.output = Output::Svg,
+            "--solver" => opts.solver = parse_solver(it.next())?,
+            "--progress" => opts.progress = true,
+            "--trace" => opts.trace = Some(it.next().ok_or(CliError::Usage)?.clone()),
+            "--animate" => {
+                let mode = it.next().ok_or(CliError::Usage)?;
+                opts.animate = Some(animate::AnimateMode::parse(mode)
+                    .ok_or_else(|| CliError::Other(format!("Unknown animation '{mode}' (expected solution or search).")))?);
+            }
+            "--delay" => {
+                let ms = it.next().ok_or(CliError::Usage)?;
+                let invalid = || CliError::Other(format!("Invalid --delay '{ms}', expected milliseconds up to {MAX_DELAY_MS}."));
+                opts.delay_ms = Some(ms.parse().ok().filter(|&ms| ms <= MAX_DELAY_MS).ok_or_else(invalid)?);
+            }
+            "-" => positional.push(a.clone()),
+            _ if a.starts_with('-') => return Err(CliError::UnknownFlag(a.clone())),
+            _ => positional.push(a.clone()),
+        }
+    }
+    if positional.len() != 1 { return Err(CliError::WrongArity(positional.len())); }
+    match (&opts.animate, &opts.output) {
+        (None, _) if opts.delay_ms.is_some() => return Err(CliError::Other("--delay only applies to --animate.".into())),
+        (Some(_), Output::Json) => return Err(CliError::Other("--animate draws the board or --svg, not --json.".into())),
+        (Some(_), _) if opts.trace.is_some() => return Err(CliError::Other("--animate cannot be combined with --trace.".into())),
+        _ => {}
+    }
+    let path = &positional[0];
+    if text_format::is_text_path(path) {
+        let parsed = GridFile::from_path(path).map_err(|e| e.to_string());
+        return solve_stream(std::iter::once(parsed), &opts, out);
+    }
+    let reader: Box<dyn Read + '_> = if path == "-" {
+        Box::new(input)
+    } else {
+        Box::new(BufReader::new(File::open(path).map_err(|e| CliError::Other(e.to_string()))?))
+    };
+    solve_stream(grid::read_grid_files(reader), &opts, out)
+}
+
+/// What the default solve command prints for a solved puzzle.
+enum Output {
+    /// ASCII board (`--no-color` decides colouring).
+    Board,
+    /// Puzzle plus solution document (`--json`).
+    Json,
+    /// SVG drawing of the solved board (`--svg`).
+    Svg,
+}
+
+/// Flags of the default solve command.
+struct SolveArgs {
+    color: bool,
+    output: Output,
+    solver: &'static dyn Solver,
+    /// Write a search trace to this file (`--trace`).
+    trace: Option<String>,
+    /// Animate the solution or the search instead of printing the final board (`--animate`).
+    animate: Option<animate::AnimateMode>,
+    /// Time per animation frame (`--delay`, default `DEFAULT_DELAY_MS`).
+    delay_ms: Option<u64>,
+    /// Report search progress on stderr (`--progress`).
+    progress: bool,
+}
+
+const DEFAULT_DELAY_MS: u64 = 300;
+/// Longest `--delay`: a minute per frame.
+const MAX_DELAY_MS: u64 = 60_000;
+/// Time between `--progress` reports.
+const PROGRESS_EVERY: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Print progress reports on stderr. On a terminal a single line is redrawn in place.
+fn progress_to_stderr() -> progress::ProgressSink {
+    let terminal = std::io::stderr().is_terminal();
+    Box::new(move |p| match (terminal, p.finished) {
+        (true, false) => eprint!("\r\x1b[Kprogress: {p}"),
+        (true, true) => eprintln!("\r\x1b[Kdone: {p}"),
+        (false, false) => eprintln!("progress: {p}"),
+        (false, true) => eprintln!("done: {p}"),
+    })
+}
+
+/// Value of `--solver`.
+fn parse_solver(name: Option<&String>) -> Result<&'static dyn Solver, CliError> {
+    let name = name.ok_or(CliError::Usage)?;
+    solver::by_name(name).ok_or_else(|| {
+        let known: Vec<&str> = solver::SOLVERS.iter().map(|s| s.name()).collect();
+        CliError::Other(format!("Unknown solver '{name}' (expected one of: {}).", known.join(", ")))
+    })
+}
+
+/// Solve every puzzle in `reader` in turn, writing each board as soon as it is found. A single puzzle prints exactly
+/// one board; further puzzles are separated by a blank line, unsolvable ones print "No solution found." in place and
+/// ones that fail to load print "Invalid puzzle." (with the error on stderr) without stopping the stream.
+fn solve_stream(puzzles: impl Iterator<Item = Result<GridFile, String>>, opts: &SolveArgs, out: &mut dyn Write) -> Result<(), CliError> {
+    let mut count = 0usize;
+    let mut unsolvable = 0usize;
+    let mut invalid = 0usize;
+    // What the first puzzle prints in place of a board, held back until we know more puzzles follow, and its error.
+    let mut first_placeholder: Option<(&str, Option<String>)> = None;
+    let trace_file = match &opts.trace {
+        Some(path) => Some(Arc::new(Mutex::new(BufWriter::new(File::create(path).map_err(|e| CliError::Other(format!("{path}: {e}")))?)))),
+        None => None,
+    };
+    for parsed in puzzles {
+        if count == 1 && let Some((placeholder, error)) = first_placeholder.take() {
+            if let Some(error) = error { eprintln!("{error}"); }
+            write_out(out, placeholder)?;
+        }
+        if count > 0 { write_out(out, "\n")?; }
+        count += 1;
+        let parsed = match parsed {
+            Ok(parsed) => parsed,
+            Err(error) => {
+                invalid += 1;
+                if count > 1 {
+                    eprintln!("{error}");
+                    write_out(out, "Invalid puzzle.\n")?;
+                } else {
+                    first_placeholder = Some(("Invalid puzzle.\n", Some(error)));
+                }
+                continue;
+            }
+        };
+        warn_overlaps(&parsed, None);
+        let mut g = GameGrid::from_parsed(parsed.clone());
+        if let Some(file) = &trace_file {
+            let file = file.clone();
+            let write_event = move |e: &trace::TraceEvent| {
+                let mut w = file.lock().unwrap();
+                // Write errors resurface when the file is flushed below.
+                serde_json::to_writer(&mut *w, e).ok();
+                w.write_all(b"\n").ok();
+            };
+            write_event(&trace::TraceEvent::Start { puzzle: Box::new(parsed.clone()) });
+            g.set_trace(Some(Box::new(write_event)));
+        }
+        if opts.progress { g.set_progress(Some(progress_to_stderr()), PROGRESS_EVERY); }
+        if let Some(mode) = opts.animate {
+            let Some(frames) = animate::frames(&mut g, opts.solver, mode) else {
+                unsolvable += 1;
+                if count > 1 { write_out(out, "No solution found.\n")?; } else { first_placeholder = Some(("No solution found.\n", None)); }
+                continue;
+            };
+            let delay_ms = opts.delay_ms.unwrap_or(DEFAULT_DELAY_MS);
+            match opts.output {
+                Output::Svg => write_out(out, &svg::render_animation(&mut g, &frames, delay_ms).map_err(CliError::Other)?)?,
+                _ => play_frames(&mut g, &frames, opts.color, delay_ms, out)?,
+            }
+        } else if opts.solver.solve(&mut g, &SolveOptions::default()).0.is_some() {
+            let rendered = match opts.output {
+                Output::Board => g.ascii_board(opts.color),
+                Output::Json => json_format::write_solved(&parsed, &g.placements()),
+                Output::Svg => svg::render(&g),
+            };
+            write_out(out, &rendered)?;
+        } else {
+            unsolvable += 1;
+            if count > 1 { write_out(out, "No solution found.\n")?; } else { first_placeholder = Some(("No solution found.\n", None)); }
+        }
+        out.flush().map_err(|e| CliError::Other(e.to_string()))?;
+    }
+    if let Some(file) = trace_file {
+        file.lock().unwrap().flush().map_err(|e| CliError::Other(format!("Writing the trace failed: {e}")))?;
+    }
+    if count == 0 { return Err(CliError::Other("No puzzle found in input.".into())); }
+    // A lone puzzle that failed to load reports just its error, as before streams were read.
+    if let Some((_, Some(error))) = first_placeholder { return Err(CliError::Other(error)); }
+    if invalid > 0 { return Err(CliError::Other(format!("{invalid} puzzle(s) failed to load."))); }
+    if unsolvable > 0 { Err(CliError::Unsolvable) } else { Ok(()) }
+}
+
+/// Draw `frames` one after another, `delay_ms` apart. In colour each frame replaces the last on screen; without colour
+/// they are separated by blank lines, for logs and pipes.
+fn play_frames(g: &mut GameGrid, frames: &[Vec<grid::Placement>], color: bool, delay_ms: u64, out: &mut dyn Write) -> Result<(), CliError> {
+    for (i, frame) in frames.iter().enumerate() {
+        if i > 0 {
+            std::thread::sleep(std::time::Duration::from_millis(delay_ms));
+            if !color { write_out(out, "\n")?; }
+        }
+        g.apply_placements(frame).map_err(CliError::Other)?;
+        if color { write_out(out, "\x1b[H\x1b[2J")?; }
+        write_out(out, &g.ascii_board(color))?;
+        out.flush().map_err(|e| CliError::Other(e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// `batch [--jobs N] [--jsonl] [--solver NAME] <file|dir|glob>...`: solve many puzzles and print a summary.
+fn run_batch(args: &[String], out: &mut dyn Write) -> Result<(), CliError> {
+    let mut jobs = 1usize;
+    let mut solver = solver::SOLVERS[0];
+    let mut format = BatchFormat::Table;
+    let mut inputs: Vec<String> = Vec::new();
+    let mut it = args.iter();
+    while let Some(a) = it.next() {
+        match a.as_str() {
+            "--jobs" | "-j" => {
+                let n = it.next().ok_or(CliError::Usage)?;
+                jobs = n.parse().map_err(|_| CliError::Other(format!("Invalid job count: {n}")))?;
+                if jobs == 0 { jobs = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1); }
+            }
+            "--jsonl" => format = BatchFormat::Jsonl,
+            "--solver" => solver = parse_solver(it.next())?,
+            _ if a.starts_with('-') => return Err(CliError::UnknownFlag(a.clone())),
+            _ => inputs.push(a.clone()),
+        }
+    }
+    if inputs.is_empty() { return Err(CliError::Usage); }
+    let paths = batch::collect_paths(&inputs).map_err(CliError::Other)?;
+    let results = batch::solve_all(&paths, jobs, solver);
+    let text = match format {
+        BatchFormat::Table => batch::render_table(&results),
+        BatchFormat::Jsonl => batch::render_jsonl(&results),
+    };
+    write_out(out, &text)?;
+    let errors = results.iter().filter(|r| matches!(r.status, batch::BatchStatus::Error(_))).count();
+    let unsolvable = results.iter().filter(|r| r.status == batch::BatchStatus::Unsolvable).count();
+    if errors + unsolvable > 0 { return Err(CliError::BatchFailed { errors, unsolvable }); }
+    Ok(())
+}
+
+/// `convert [--to text|json] <puzzle>`: print the puzzle in the text format (default) or as canonical JSON.
+fn run_convert(args: &[String], out: &mut dyn Write) -> Result<(), CliError> {
+    let mut to_json = false;
+    let mut positional: Vec<String> = Vec::new();
+    let mut it = args.iter();
+    while let Some(a) = it.next() {
+        match a.as_str() {
+            "--to" => to_json = parse_target_format(it.next())?,
+            _ if a.starts_with('-') => return Err(CliError::UnknownFlag(a.clone())),
+            _ => positional.push(a.clone()),
+        }
+    }
+    if positional.len() != 1 { return Err(CliError::WrongArity(positional.len())); }
+    let parsed = load_puzzle(&positional[0])?;
+    write_puzzle(&parsed, to_json, out)
+}
+
+/// `verify [--no-color] <solved.json>`: check the solution stored in a puzzle + solution document and print its board.
+fn run_verify(args: &[String], out: &mut dyn Write) -> Result<(), CliError> {
     let mut color = true;
     let mut positional: Vec<String> = Vec::new();
-    for a in args.iter() {
+    for a in args {
         match a.as_str() {
             "--no-color" | "--no-colors" | "-nc" => color = false,
             _ if a.starts_with('-') => return Err(CliError::UnknownFlag(a.clone())),
@@ -24,26 +303,336 @@ pub fn run_cli(args: &[String]) -> Result<String, CliError> {
         }
     }
     if positional.len() != 1 { return Err(CliError::WrongArity(positional.len())); }
-    let path = &positional[0];
-    let mut g = GameGrid::from_file(path).map_err(|e| CliError::Other(e.to_string()))?;
-    if g.solve().is_some() {
-        Ok(g.ascii_board(color))
+    let json = std::fs::read_to_string(&positional[0]).map_err(|e| CliError::Other(e.to_string()))?;
+    let solved = json_format::read_solved(&json).map_err(CliError::Other)?;
+    warn_overlaps(&solved.puzzle, None);
+    let mut g = GameGrid::from_parsed(solved.puzzle);
+    g.verify_placements(&solved.solution).map_err(CliError::Other)?;
+    write_out(out, &g.ascii_board(color))?;
+    write_out(out, "Solution is valid.\n")
+}
+
+/// `count [--solver NAME] [--checkpoint FILE [--every SECS]] <puzzle>` or `count --resume FILE [--every SECS]`: count
+/// every solution. With a checkpoint file the count is saved there every SECS (default 60) and on Ctrl-C, `--resume`
+/// continues it, and the file is removed once the count is complete.
+fn run_count(args: &[String], out: &mut dyn Write) -> Result<(), CliError> {
+    let mut solver = None;
+    let mut save_to: Option<String> = None;
+    let mut resume: Option<String> = None;
+    let mut every = 60u64;
+    let mut positional: Vec<&String> = Vec::new();
+    let mut it = args.iter();
+    while let Some(a) = it.next() {
+        match a.as_str() {
+            "--solver" => solver = Some(parse_solver(it.next())?),
+            "--checkpoint" => save_to = Some(it.next().ok_or(CliError::Usage)?.clone()),
+            "--resume" => resume = Some(it.next().ok_or(CliError::Usage)?.clone()),
+            "--every" => {
+                let secs = it.next().ok_or(CliError::Usage)?;
+                every = secs.parse().map_err(|_| CliError::Other(format!("Invalid --every '{secs}', expected seconds.")))?;
+            }
+            _ if a.starts_with('-') => return Err(CliError::UnknownFlag(a.clone())),
+            _ => positional.push(a),
+        }
+    }
+    let (mut count, file) = match (resume, positional.as_slice()) {
+        (Some(_), _) if save_to.is_some() || solver.is_some() =>
+            return Err(CliError::Other("--resume takes the puzzle, solver and file from the checkpoint.".into())),
+        (Some(file), []) => (checkpoint::Checkpoint::load(&file).map_err(CliError::Other)?, file),
+        (None, [puzzle]) => {
+            let parsed = load_puzzle(puzzle)?;
+            let solver = solver.unwrap_or(solver::SOLVERS[0]);
+            let Some(file) = save_to else {
+                let stats = solver.enumerate(&mut GameGrid::from_parsed(parsed), &SolveOptions::default(), &mut |_| false);
+                return write_out(out, &solutions_line(stats.solutions));
+            };
+            (checkpoint::Checkpoint::new(parsed, solver), file)
+        }
+        (_, positional) => return Err(CliError::WrongArity(positional.len())),
+    };
+    let stop = Arc::new(AtomicBool::new(false));
+    stop_on_ctrl_c(stop.clone());
+    if !count.run(std::time::Duration::from_secs(every), &stop, &mut |c| c.save(&file)).map_err(CliError::Other)? {
+        return Err(CliError::Other(format!("Interrupted after {}; continue with: pips_solver count --resume {file}",
+            solutions_line(count.solutions).trim_end())));
+    }
+    match std::fs::remove_file(&file) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(CliError::Other(format!("{file}: {e}"))),
+        _ => {}
+    }
+    write_out(out, &solutions_line(count.solutions))
+}
+
+fn solutions_line(n: usize) -> String {
+    if n == 1 { "1 solution\n".into() } else { format!("{n} solutions\n") }
+}
+
+/// Set `flag` on the first Ctrl-C; a second one ends the process as usual.
+#[cfg(unix)]
+fn stop_on_ctrl_c(flag: Arc<AtomicBool>) {
+    static FLAG: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+    const SIGINT: i32 = 2;
+    const SIG_DFL: usize = 0;
+    unsafe extern "C" {
+        fn signal(signum: i32, handler: usize) -> usize;
+    }
+    extern "C" fn on_sigint(_: i32) {
+        if let Some(flag) = FLAG.get() { flag.store(true, Ordering::Relaxed); }
+        unsafe { signal(SIGINT, SIG_DFL); }
+    }
+    if FLAG.set(flag).is_ok() {
+        unsafe { signal(SIGINT, on_sigint as extern "C" fn(i32) as usize); }
+    }
+}
+
+/// Only the periodic checkpoints are written on other platforms.
+#[cfg(not(unix))]
+fn stop_on_ctrl_c(_flag: Arc<AtomicBool>) {}
+
+/// `dedupe [--symmetric] <file|dir|glob>...`: list groups of files holding the same puzzle (see `canonical`), each line
+/// giving the puzzle hash and a file, followed by a summary.
+fn run_dedupe(args: &[String], out: &mut dyn Write) -> Result<(), CliError> {
+    let mut symmetric = false;
+    let mut inputs: Vec<String> = Vec::new();
+    for a in args {
+        match a.as_str() {
+            "--symmetric" => symmetric = true,
+            _ if a.starts_with('-') => return Err(CliError::UnknownFlag(a.clone())),
+            _ => inputs.push(a.clone()),
+        }
+    }
+    if inputs.is_empty() { return Err(CliError::Usage); }
+    let paths = batch::collect_paths(&inputs).map_err(CliError::Other)?;
+    let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+    let mut errors = 0usize;
+    for path in &paths {
+        let name = path.to_string_lossy().into_owned();
+        match GridFile::from_path(&name) {
+            Ok(file) => {
+                warn_overlaps(&file, Some(&name));
+                let hash = canonical::puzzle_hash(&file, symmetric);
+                match groups.iter_mut().find(|(h, _)| *h == hash) {
+                    Some((_, members)) => members.push(name),
+                    None => groups.push((hash, vec![name])),
+                }
+            }
+            Err(e) => {
+                eprintln!("{name}: {e}");
+                errors += 1;
+            }
+        }
+    }
+    let mut text = String::new();
+    for (hash, members) in groups.iter().filter(|(_, m)| m.len() > 1) {
+        for name in members { text.push_str(&format!("{hash}  {name}\n")); }
+        text.push('\n');
+    }
+    let loaded = paths.len() - errors;
+    let duplicates = loaded - groups.len();
+    text.push_str(&format!("{loaded} puzzles, {} distinct, {duplicates} duplicate{}\n", groups.len(), if duplicates == 1 { "" } else { "s" }));
+    write_out(out, &text)?;
+    if errors > 0 { return Err(CliError::Other(format!("{errors} puzzle(s) failed to load."))); }
+    Ok(())
+}
+
+/// `cnf [--model <file>] [--no-color] <puzzle>`: print the puzzle as DIMACS CNF, or with `--model` read a SAT solver's
+/// model for that CNF and print the decoded board.
+fn run_cnf(args: &[String], out: &mut dyn Write) -> Result<(), CliError> {
+    let mut color = true;
+    let mut model: Option<String> = None;
+    let mut positional: Vec<String> = Vec::new();
+    let mut it = args.iter();
+    while let Some(a) = it.next() {
+        match a.as_str() {
+            "--no-color" | "--no-colors" | "-nc" => color = false,
+            "--model" => model = Some(it.next().ok_or(CliError::Usage)?.clone()),
+            _ if a.starts_with('-') => return Err(CliError::UnknownFlag(a.clone())),
+            _ => positional.push(a.clone()),
+        }
+    }
+    if positional.len() != 1 { return Err(CliError::WrongArity(positional.len())); }
+    let mut g = GameGrid::from_parsed(load_puzzle(&positional[0])?);
+    let cnf = dimacs::encode(&g);
+    let Some(model) = model else { return write_out(out, &cnf.to_dimacs()) };
+    let text = std::fs::read_to_string(&model).map_err(|e| CliError::Other(e.to_string()))?;
+    let placements = cnf.decode_model(&text).map_err(CliError::Other)?;
+    g.apply_placements(&placements).map_err(CliError::Other)?;
+    write_out(out, &g.ascii_board(color))
+}
+
+/// Parse the value of `--to`, returning whether JSON was requested.
+fn parse_target_format(value: Option<&String>) -> Result<bool, CliError> {
+    match value.map(String::as_str) {
+        Some("json") => Ok(true),
+        Some("text") => Ok(false),
+        Some(other) => Err(CliError::Other(format!("Unknown format '{other}', expected text or json."))),
+        None => Err(CliError::Usage),
+    }
+}
+
+/// Load the puzzle file a command was given, printing its overlap warnings.
+fn load_puzzle(path: &str) -> Result<GridFile, CliError> {
+    let parsed = GridFile::from_path(path).map_err(|e| CliError::Other(e.to_string()))?;
+    warn_overlaps(&parsed, None);
+    Ok(parsed)
+}
+
+/// Print `file`'s overlap warnings on stderr, prefixed with `name` when several puzzles are read.
+fn warn_overlaps(file: &GridFile, name: Option<&str>) {
+    for warning in file.overlap_warnings() {
+        match name {
+            Some(name) => eprintln!("{name}: Warning: {warning}"),
+            None => eprintln!("Warning: {warning}"),
+        }
+    }
+}
+
+fn write_puzzle(parsed: &GridFile, to_json: bool, out: &mut dyn Write) -> Result<(), CliError> {
+    if to_json { return write_out(out, &json_format::write(parsed)); }
+    let text = text_format::write(parsed).map_err(|e| CliError::Other(e.to_string()))?;
+    write_out(out, &text)
+}
+
+/// `nyt [--difficulty D] [--convert [--to text|json]] [--no-color] <file>`: import an NYT-shaped puzzle, then either print it in the text
+/// format or solve it and compare against the official solution when one is included.
+fn run_nyt(args: &[String], out: &mut dyn Write) -> Result<(), CliError> {
+    let mut color = true;
+    let mut convert = false;
+    let mut to_json = false;
+    let mut difficulty: Option<String> = None;
+    let mut positional: Vec<String> = Vec::new();
+    let mut it = args.iter();
+    while let Some(a) = it.next() {
+        match a.as_str() {
+            "--no-color" | "--no-colors" | "-nc" => color = false,
+            "--convert" => convert = true,
+            "--to" => to_json = parse_target_format(it.next())?,
+            "--difficulty" => difficulty = Some(it.next().ok_or(CliError::Usage)?.clone()),
+            _ if a.starts_with('-') => return Err(CliError::UnknownFlag(a.clone())),
+            _ => positional.push(a.clone()),
+        }
+    }
+    if positional.len() != 1 { return Err(CliError::WrongArity(positional.len())); }
+    let json = std::fs::read_to_string(&positional[0]).map_err(|e| CliError::Other(e.to_string()))?;
+    let imported = nyt::import(&json, difficulty.as_deref()).map_err(CliError::Other)?;
+    if convert { return write_puzzle(&imported.puzzle, to_json, out); }
+    let mut g = GameGrid::from_parsed(imported.puzzle.clone());
+    let Some(ours) = g.solve() else { return Err(CliError::Unsolvable) };
+    write_out(out, &g.ascii_board(color))?;
+    if let Some(official) = imported.solution_pips() {
+        if ours == official {
+            write_out(out, "Official solution: matches.\n")?;
+        } else if g.check_assignment(&official) {
+            write_out(out, "Official solution: differs (both satisfy every rule).\n")?;
+        } else {
+            return Err(CliError::Other("Official solution violates the imported rules.".into()));
+        }
+    }
+    Ok(())
+}
+
+/// `replay [--no-color] [--step] <trace.jsonl|->`: print every step of a `--trace` file with the board after it.
+/// `--step` waits for Enter after each step (`q` stops).
+fn run_replay(args: &[String], input: &mut dyn BufRead, out: &mut dyn Write) -> Result<(), CliError> {
+    let mut color = true;
+    let mut step = false;
+    let mut positional: Vec<&String> = Vec::new();
+    for a in args {
+        match a.as_str() {
+            "--no-color" | "--no-colors" | "-nc" => color = false,
+            "--step" => step = true,
+            "-" => positional.push(a),
+            _ if a.starts_with('-') => return Err(CliError::UnknownFlag(a.clone())),
+            _ => positional.push(a),
+        }
+    }
+    if positional.len() != 1 { return Err(CliError::WrongArity(positional.len())); }
+    if step && positional[0] == "-" { return Err(CliError::Other("--step reads Enter from stdin, so the trace must be a file.".into())); }
+    let reader: Box<dyn BufRead> = if positional[0] == "-" {
+        let mut text = String::new();
+        input.read_to_string(&mut text).map_err(|e| CliError::Other(e.to_string()))?;
+        Box::new(std::io::Cursor::new(text))
     } else {
-        Err(CliError::Unsolvable)
+        Box::new(BufReader::new(File::open(positional[0]).map_err(|e| CliError::Other(e.to_string()))?))
+    };
+    let mut board: Option<GameGrid> = None;
+    let mut answer = String::new();
+    for (n, event) in trace::read_trace(reader).enumerate() {
+        let event = event.map_err(CliError::Other)?;
+        if let trace::TraceEvent::Start { puzzle } = &event {
+            board = Some(GameGrid::from_parsed(GridFile::clone(puzzle)));
+        }
+        let g = board.as_mut().ok_or_else(|| CliError::Other("Trace does not begin with a start event.".into()))?;
+        g.apply_trace_event(&event).map_err(CliError::Other)?;
+        write_out(out, &format!("#{} {event}\n", n + 1))?;
+        // A prune leaves the board as the previous step drew it.
+        if !matches!(event, trace::TraceEvent::Prune { .. }) { write_out(out, &g.ascii_board(color))?; }
+        if step {
+            out.flush().map_err(|e| CliError::Other(e.to_string()))?;
+            answer.clear();
+            if input.read_line(&mut answer).map_err(|e| CliError::Other(e.to_string()))? == 0 || answer.trim() == "q" { break; }
+        }
     }
+    Ok(())
+}
+
+/// `edit <puzzle>`: interactive editor reading commands from `input` (see `editor`).
+fn run_edit(args: &[String], input: &mut dyn BufRead, out: &mut dyn Write) -> Result<(), CliError> {
+    if let Some(flag) = args.iter().find(|a| a.starts_with('-')) { return Err(CliError::UnknownFlag(flag.clone())); }
+    if args.len() != 1 { return Err(CliError::WrongArity(args.len())); }
+    let mut editor = editor::Editor::open(&args[0]).map_err(CliError::Other)?;
+    editor.run(input, out).map_err(|e| CliError::Other(e.to_string()))
+}
+
+/// `serve [--port N] [--timeout-ms N]`: answer HTTP requests on localhost until killed (see `serve`).
+fn run_serve(args: &[String], out: &mut dyn Write) -> Result<(), CliError> {
+    let mut port = 7878u16;
+    let mut timeout_ms = 5000u64;
+    let mut it = args.iter();
+    while let Some(a) = it.next() {
+        match a.as_str() {
+            "--port" => {
+                let n = it.next().ok_or(CliError::Usage)?;
+                port = n.parse().map_err(|_| CliError::Other(format!("Invalid port: {n}")))?;
+            }
+            "--timeout-ms" => {
+                let n = it.next().ok_or(CliError::Usage)?;
+                timeout_ms = n.parse().map_err(|_| CliError::Other(format!("Invalid timeout: {n}")))?;
+            }
+            _ if a.starts_with('-') => return Err(CliError::UnknownFlag(a.clone())),
+            _ => return Err(CliError::Other(format!("serve takes no puzzle path; unexpected argument '{a}'."))),
+        }
+    }
+    let listener = std::net::TcpListener::bind(("127.0.0.1", port)).map_err(|e| CliError::Other(e.to_string()))?;
+    let addr = listener.local_addr().map_err(|e| CliError::Other(e.to_string()))?;
+    // Port 0 picks a free port, so say which one was taken.
+    write_out(out, &format!("Listening on http://{addr}\n"))?;
+    out.flush().map_err(|e| CliError::Other(e.to_string()))?;
+    let config = serve::Config { timeout: std::time::Duration::from_millis(timeout_ms) };
+    serve::serve(listener, config).map_err(|e| CliError::Other(e.to_string()))
+}
+
+fn write_out(out: &mut dyn Write, text: &str) -> Result<(), CliError> {
+    out.write_all(text.as_bytes()).map_err(|e| CliError::Other(e.to_string()))
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args: Vec<String> = env::args().skip(1).collect();
-    match run_cli(&args) {
-        Ok(out) => { print!("{out}"); Ok(()) }
+    let stdout = std::io::stdout();
+    match run_cli_to(&args, &mut std::io::stdin().lock(), &mut stdout.lock()) {
+        Ok(()) => Ok(()),
         Err(err) => {
             match &err {
-                CliError::Usage => eprintln!("Usage: pips_solver [--no-color|-n|-nc|--no-colors] <path-to-grid.json>"),
+                CliError::Usage => eprintln!("Usage: pips_solver [--no-color|-n|-nc|--no-colors] [--json|--svg] [--solver backtrack|dlx] [--trace <file>] [--progress] [--animate solution|search [--delay MS]] <path-to-grid.json|->\n       pips_solver batch [--jobs N] [--jsonl] [--solver backtrack|dlx] <file|dir|glob>...\n       pips_solver convert [--to text|json] <puzzle>\n       pips_solver verify <solved.json>\n       pips_solver cnf [--model <sat-output>] <puzzle>\n       pips_solver count [--solver backtrack|dlx] [--checkpoint FILE [--every SECS]] <puzzle>\n       pips_solver count --resume FILE [--every SECS]\n       pips_solver dedupe [--symmetric] <file|dir|glob>...\n       pips_solver nyt [--difficulty easy|medium|hard] [--convert [--to text|json]] <nyt.json>\n       pips_solver serve [--port N] [--timeout-ms N]\n       pips_solver edit <puzzle>\n       pips_solver replay [--no-color] [--step] <trace.jsonl|->"),
                 CliError::UnknownFlag(f) => eprintln!("Unknown flag: {f}"),
                 CliError::WrongArity(n) => eprintln!("Expected exactly one JSON path. Got {n}."),
                 CliError::Other(msg) => eprintln!("{msg}"),
                 CliError::Unsolvable => { eprintln!("No solution found."); std::process::exit(2); }
+                CliError::BatchFailed { errors, unsolvable } => {
+                    eprintln!("{errors} puzzle(s) failed to load, {unsolvable} unsolvable.");
+                    // load errors take precedence over unsolvable puzzles
+                    std::process::exit(if *errors > 0 { 1 } else { 2 });
+                }
             }
             // map all but Unsolvable to exit code 1
             if !matches!(err, CliError::Unsolvable) { std::process::exit(1); }
@@ -69,6 +658,12 @@ mod tests {
     #[test]
     fn cli_wrong_arity_branch() { assert_eq!(run_cli(&[fixture("easy_grid.json"), fixture("medium_grid.json")]), Err(CliError::WrongArity(2))); }
 
+    #[test]
+    fn cli_serve_rejects_a_path() {
+        let res = run_cli(&["serve".into(), fixture("easy_grid.json")]);
+        assert!(matches!(&res, Err(CliError::Other(m)) if m.contains("serve takes no puzzle path")), "{res:?}");
+    }
+
     #[test]
     fn cli_unsolvable_branch() {
         let res = run_cli(&[fixture("unsolvable_grid.json")]);
@@ -82,4 +677,158 @@ mod tests {
         let out_plain = run_cli(&["--no-color".into(), fixture("easy_grid.json")]).expect("should solve");
         assert!(!out_plain.contains("\x1b["));
     }
+
+    #[test]
+    fn cli_solver_selection() {
+        let dlx = run_cli(&["--no-color".into(), "--solver".into(), "dlx".into(), fixture("easy_grid.json")]).expect("should solve");
+        let bt = run_cli(&["--no-color".into(), fixture("easy_grid.json")]).unwrap();
+        assert_eq!(dlx.lines().count(), bt.lines().count());
+        assert_eq!(run_cli(&["--solver".into(), "dlx".into(), fixture("unsolvable_grid.json")]), Err(CliError::Unsolvable));
+        let res = run_cli(&["--solver".into(), "magic".into(), fixture("easy_grid.json")]);
+        assert!(matches!(res, Err(CliError::Other(ref m)) if m.contains("backtrack, dlx")), "{res:?}");
+        assert_eq!(run_cli(&[fixture("easy_grid.json"), "--solver".into()]), Err(CliError::Usage));
+    }
+
+    #[test]
+    fn cli_svg_output() {
+        let svg = run_cli(&["--svg".into(), fixture("hex_grid.json")]).expect("should solve");
+        assert!(svg.starts_with("<svg") && svg.contains("<polygon"), "{svg}");
+        assert_eq!(svg.matches("<line").count(), 2, "one bar per domino");
+    }
+
+    #[test]
+    fn cli_animate_flags() {
+        let args = |extra: &[&str]| -> Vec<String> { extra.iter().map(|s| s.to_string()).chain([fixture("easy_grid.json")]).collect() };
+        let frames = run_cli(&args(&["--no-color", "--animate", "solution", "--delay", "0"])).expect("should solve");
+        let solved = run_cli(&args(&["--no-color"])).unwrap();
+        assert!(frames.ends_with(&solved) && frames.len() > 2 * solved.len(), "{frames}");
+        let colored = run_cli(&args(&["--animate", "search", "--delay", "0"])).unwrap();
+        assert!(colored.starts_with("\x1b[H\x1b[2J"));
+        let svg = run_cli(&args(&["--svg", "--animate", "solution", "--delay", "0"])).unwrap();
+        assert!(svg.contains("<set attributeName=\"visibility\""));
+        for (extra, message) in [(&["--animate", "slow"][..], "Unknown animation"), (&["--delay", "5"], "only applies"),
+                (&["--json", "--animate", "search"], "not --json"), (&["--animate", "search", "--delay", "soon"], "Invalid --delay"),
+                (&["--animate", "search", "--delay", "60001"], "up to 60000")] {
+            let res = run_cli(&args(extra));
+            assert!(matches!(res, Err(CliError::Other(ref m)) if m.contains(message)), "{extra:?}: {res:?}");
+        }
+    }
+
+    #[test]
+    fn cli_count_and_resume() {
+        assert_eq!(run_cli(&["count".into(), fixture("unsolvable_grid.json")]), Ok("0 solutions\n".into()));
+        let all = run_cli(&["count".into(), "--solver".into(), "dlx".into(), fixture("medium_grid.json")]).unwrap();
+        let file = std::env::temp_dir().join(format!("pips_count_{}.json", std::process::id()));
+        let file = file.to_string_lossy().into_owned();
+        let fresh = checkpoint::Checkpoint::new(GridFile::from_path(&fixture("medium_grid.json")).unwrap(), solver::SOLVERS[1]);
+        fresh.save(&file).unwrap();
+        assert_eq!(run_cli(&["count".into(), "--resume".into(), file.clone()]), Ok(all));
+        assert!(!std::path::Path::new(&file).exists(), "a finished count removes its checkpoint");
+        assert_eq!(run_cli(&["count".into()]), Err(CliError::WrongArity(0)));
+        let res = run_cli(&["count".into(), "--resume".into(), file.clone(), "--solver".into(), "dlx".into()]);
+        assert!(matches!(res, Err(CliError::Other(ref m)) if m.contains("from the checkpoint")), "{res:?}");
+    }
+
+    #[test]
+    fn cli_batch_reports_failures_after_summary() {
+        let mut out = Vec::new();
+        let res = run_cli_to(&["batch".into(), fixture("easy_grid.json"), fixture("unsolvable_grid.json")], &mut std::io::empty(), &mut out);
+        assert_eq!(res, Err(CliError::BatchFailed { errors: 0, unsolvable: 1 }));
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("unsolvable") && text.contains("1/2 solved"));
+        assert_eq!(run_cli(&["batch".into(), fixture("easy_grid.json")]).map(|s| s.contains("1/1 solved")), Ok(true));
+        assert_eq!(run_cli(&["batch".into()]), Err(CliError::Usage));
+    }
+
+    #[test]
+    fn cli_stdin_jsonl_stream() {
+        let easy = std::fs::read_to_string(fixture("easy_grid.json")).unwrap();
+        let unsolvable = std::fs::read_to_string(fixture("unsolvable_grid.json")).unwrap();
+        let compact = |s: &str| serde_json::from_str::<serde_json::Value>(s).unwrap().to_string();
+        let input = format!("{}\n{}\n{}\n", compact(&unsolvable), compact(&easy), compact(&easy));
+        let mut out = Vec::new();
+        let res = run_cli_to(&["-nc".into(), "-".into()], &mut input.as_bytes(), &mut out);
+        assert_eq!(res, Err(CliError::Unsolvable));
+        let text = String::from_utf8(out).unwrap();
+        let blocks: Vec<&str> = text.split("\n\n").collect();
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0], "No solution found.");
+        assert_eq!(blocks[1], blocks[2].trim_end_matches('\n'));
+        // A single pretty-printed document on stdin behaves like a file argument.
+        let mut single = Vec::new();
+        run_cli_to(&["-nc".into(), "-".into()], &mut easy.as_bytes(), &mut single).unwrap();
+        assert_eq!(String::from_utf8(single).unwrap(), run_cli(&["-nc".into(), fixture("easy_grid.json")]).unwrap());
+    }
+
+    #[test]
+    fn cli_stream_skips_a_malformed_record() {
+        let easy = std::fs::read_to_string(fixture("easy_grid.json")).unwrap();
+        let easy = serde_json::from_str::<serde_json::Value>(&easy).unwrap().to_string();
+        let input = format!("{easy}\n{{\"grid\": [oops\n{easy}\n");
+        let mut out = Vec::new();
+        let res = run_cli_to(&["-nc".into(), "-".into()], &mut input.as_bytes(), &mut out);
+        assert_eq!(res, Err(CliError::Other("1 puzzle(s) failed to load.".into())));
+        let text = String::from_utf8(out).unwrap();
+        let blocks: Vec<&str> = text.split("\n\n").collect();
+        assert_eq!(blocks.len(), 3, "{text}");
+        assert_eq!(blocks[1], "Invalid puzzle.");
+        assert_eq!(blocks[0], blocks[2].trim_end_matches('\n'));
+        // On its own, a bad document still fails with its parse error.
+        let res = run_cli_to(&["-".into()], &mut "{\"grid\": [oops\n".as_bytes(), &mut Vec::new());
+        assert!(matches!(res, Err(CliError::Other(ref m)) if m.starts_with("line 1: ")), "{res:?}");
+    }
+
+    #[test]
+    fn cli_nyt_import() {
+        let daily = format!("{}/tests/nyt/daily_sample.json", env!("CARGO_MANIFEST_DIR"));
+        let out = run_cli(&["nyt".into(), "-nc".into(), "--difficulty".into(), "medium".into(), daily.clone()]).unwrap();
+        assert!(out.starts_with(&run_cli(&["-nc".into(), fixture("medium_grid.json")]).unwrap()));
+        assert!(out.contains("Official solution: matches."));
+        let text = run_cli(&["nyt".into(), "--convert".into(), "--difficulty".into(), "easy".into(), daily.clone()]).unwrap();
+        assert_eq!(text, run_cli(&["convert".into(), fixture("easy_grid.json")]).unwrap());
+        assert!(matches!(run_cli(&["nyt".into(), daily]), Err(CliError::Other(_))));
+    }
+
+    #[test]
+    fn cli_json_output_and_convert() {
+        let doc = run_cli(&["--json".into(), fixture("easy_grid.json")]).unwrap();
+        let solved = json_format::read_solved(&doc).unwrap();
+        assert_eq!(solved.solution.len(), 4);
+        let canonical = run_cli(&["convert".into(), "--to".into(), "json".into(), fixture("easy_grid.pips")]).unwrap();
+        assert_eq!(canonical, json_format::write(&GridFile::from_path(&fixture("easy_grid.json")).unwrap()));
+        assert!(matches!(run_cli(&["convert".into(), "--to".into(), "yaml".into(), fixture("easy_grid.json")]), Err(CliError::Other(_))));
+        let path = std::env::temp_dir().join(format!("pips_cli_solved_{}.json", std::process::id()));
+        std::fs::write(&path, &doc).unwrap();
+        let verified = run_cli(&["verify".into(), "-nc".into(), path.to_string_lossy().into_owned()]);
+        std::fs::write(&path, doc.replace("\"pips\": [3, 1]", "\"pips\": [1, 3]")).unwrap();
+        let broken = run_cli(&["verify".into(), path.to_string_lossy().into_owned()]);
+        std::fs::remove_file(&path).ok();
+        assert!(verified.unwrap().ends_with("Solution is valid.\n"));
+        assert!(matches!(broken, Err(CliError::Other(_))));
+    }
+
+    #[test]
+    fn cli_cnf_export_and_model() {
+        let cnf = run_cli(&["cnf".into(), fixture("not_equal_grid.json")]).unwrap();
+        assert!(cnf.lines().any(|l| l.starts_with("p cnf ")));
+        // Pick the first placement variable and mark it true; the pips follow from the placement itself.
+        let place = cnf.lines().find(|l| l.starts_with("c place ")).unwrap();
+        let var = place.split_whitespace().nth(2).unwrap();
+        let path = std::env::temp_dir().join(format!("pips_cli_model_{}.txt", std::process::id()));
+        std::fs::write(&path, format!("s SATISFIABLE\nv {var} 0\n")).unwrap();
+        let board = run_cli(&["cnf".into(), "-nc".into(), "--model".into(), path.to_string_lossy().into_owned(), fixture("not_equal_grid.json")]);
+        std::fs::remove_file(&path).ok();
+        let board = board.unwrap();
+        assert!(board.contains('1') && board.contains('2'));
+    }
+
+    #[test]
+    fn cli_text_format_matches_json() {
+        let text = run_cli(&["convert".into(), fixture("medium_grid.json")]).unwrap();
+        let path = std::env::temp_dir().join(format!("pips_cli_{}.pips", std::process::id()));
+        std::fs::write(&path, text).unwrap();
+        let from_text = run_cli(&["-nc".into(), path.to_string_lossy().into_owned()]);
+        std::fs::remove_file(&path).ok();
+        assert_eq!(from_text, run_cli(&["-nc".into(), fixture("medium_grid.json")]));
+    }
 }
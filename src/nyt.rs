@@ -0,0 +1,160 @@
+//! Importer for puzzles in the shape published by the NYT pips game.
+//!
+//! A puzzle there lists `dominoes`, `regions` (each with `indices`, a `type` and an optional `target`) and the official
+//! `solution` as one cell pair per domino. Indices are `[row, col]` with row 0 at the top; they are flipped into this
+//! crate's bottom-left `(1,1)` origin. The daily document wraps one puzzle per difficulty (`easy`, `medium`, `hard`).
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::grid::{Coord, Domino, GridEntry, GridFile};
+
+/// Difficulty keys of the daily document, in the order they are offered.
+pub const DIFFICULTIES: [&str; 3] = ["easy", "medium", "hard"];
+
+#[derive(Deserialize)]
+struct RawPuzzle {
+    dominoes: Vec<Domino>,
+    regions: Vec<RawRegion>,
+    #[serde(default)]
+    solution: Option<Vec<[[u32; 2]; 2]>>,
+}
+
+#[derive(Deserialize)]
+struct RawRegion {
+    indices: Vec<[u32; 2]>,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    target: Option<u32>,
+}
+
+/// An imported puzzle plus the official solution when the source carried one.
+#[derive(Debug, Clone)]
+pub struct NytImport {
+    pub puzzle: GridFile,
+    /// Cells covered by each domino, parallel to `puzzle.dominoes`: the first pip lies on the first cell.
+    pub solution: Option<Vec<(Coord, Coord)>>,
+}
+
+impl NytImport {
+    /// Pip value per cell according to the official solution.
+    pub fn solution_pips(&self) -> Option<HashMap<Coord, u8>> {
+        let placements = self.solution.as_ref()?;
+        let mut pips = HashMap::new();
+        for (&(a, b), &(pa, pb)) in placements.iter().zip(&self.puzzle.dominoes) {
+            pips.insert(a, pa);
+            pips.insert(b, pb);
+        }
+        Some(pips)
+    }
+}
+
+/// Import a single puzzle or pick `difficulty` out of a daily document. Without a difficulty a daily document is only
+/// accepted if it holds exactly one puzzle.
+pub fn import(json: &str, difficulty: Option<&str>) -> Result<NytImport, String> {
+    let value: serde_json::Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let puzzle = if value.get("regions").is_some() {
+        value
+    } else {
+        let available: Vec<&str> = DIFFICULTIES.iter().copied().filter(|d| value.get(d).is_some()).collect();
+        let chosen = match (difficulty, available.as_slice()) {
+            (Some(d), _) if available.contains(&d) => d,
+            (Some(d), _) => return Err(format!("No '{d}' puzzle in document (available: {}).", available.join(", "))),
+            (None, [only]) => only,
+            (None, []) => return Err("Not an NYT pips puzzle: no 'regions' or difficulty keys.".into()),
+            (None, _) => return Err(format!("Document holds several puzzles ({}); choose one with --difficulty.", available.join(", "))),
+        };
+        value[chosen].clone()
+    };
+    let raw: RawPuzzle = serde_json::from_value(puzzle).map_err(|e| e.to_string())?;
+    convert(raw)
+}
+
+fn convert(raw: RawPuzzle) -> Result<NytImport, String> {
+    let solution_cells = raw.solution.iter().flatten().flatten();
+    let max_row = raw.regions.iter().flat_map(|r| r.indices.iter()).chain(solution_cells).map(|&[row, _]| row).max().unwrap_or(0);
+    // Row 0 is the top row; our y grows upward from 1.
+    let flip = |[row, col]: [u32; 2]| -> Coord { (col + 1, max_row - row + 1) };
+    let mut grid = Vec::with_capacity(raw.regions.len());
+    for region in &raw.regions {
+        grid.push(GridEntry { rule: rule_token(&region.kind, region.target)?, coords: region.indices.iter().map(|&i| flip(i)).collect(), ..Default::default() });
+    }
+    let solution = match raw.solution {
+        Some(pairs) if pairs.len() != raw.dominoes.len() => {
+            return Err(format!("Solution lists {} placements for {} dominoes.", pairs.len(), raw.dominoes.len()));
+        }
+        Some(pairs) => Some(pairs.into_iter().map(|[a, b]| (flip(a), flip(b))).collect()),
+        None => None,
+    };
+    Ok(NytImport { puzzle: GridFile { grid, dominoes: raw.dominoes, ..Default::default() }, solution })
+}
+
+/// Map an NYT region type (and target) onto this crate's rule token. Also used for structured rules in puzzle files.
+pub fn rule_token(kind: &str, target: Option<u32>) -> Result<String, String> {
+    let target = || target.ok_or_else(|| format!("Region type '{kind}' needs a target."));
+    Ok(match kind {
+        "empty" => "x".into(),
+        "equals" => "=".into(),
+        "unequal" => "!=".into(),
+        "sum" => target()?.to_string(),
+        "less" => format!("<{}", target()?),
+        "greater" => format!(">{}", target()?),
+        other => return Err(format!("Unsupported region type '{other}'.")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::GameGrid;
+    use crate::grid::test_support::fixture;
+
+    fn sample(name: &str) -> String {
+        std::fs::read_to_string(format!("{}/tests/nyt/{name}", env!("CARGO_MANIFEST_DIR"))).unwrap()
+    }
+
+    #[test]
+    fn daily_document_matches_fixtures() {
+        let json = sample("daily_sample.json");
+        let easy = import(&json, Some("easy")).unwrap();
+        assert_eq!(easy.puzzle, fixture("easy_grid.json"));
+        let medium = import(&json, Some("medium")).unwrap();
+        assert_eq!(medium.puzzle, fixture("medium_grid.json"));
+        assert!(import(&json, None).unwrap_err().contains("--difficulty"));
+        assert!(import(&json, Some("hard")).unwrap_err().contains("available: easy, medium"));
+    }
+
+    #[test]
+    fn official_solution_cross_checks() {
+        for (name, difficulty) in [("daily_sample.json", Some("easy")), ("daily_sample.json", Some("medium")), ("single_sample.json", None)] {
+            let imported = import(&sample(name), difficulty).unwrap();
+            let official = imported.solution_pips().expect("sample carries a solution");
+            let mut g = GameGrid::from_parsed(imported.puzzle.clone());
+            assert!(g.check_assignment(&official), "official solution should satisfy the imported rules");
+            let ours = g.solve().expect("imported puzzle should solve");
+            assert!(g.check_assignment(&ours));
+            // Every official placement must be an orthogonally adjacent pair.
+            for &((ax, ay), (bx, by)) in imported.solution.as_ref().unwrap() {
+                assert_eq!(ax.abs_diff(bx) + ay.abs_diff(by), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn rule_mapping_and_errors() {
+        let json = r#"{"dominoes": [[1, 2]], "regions": [
+            {"indices": [[0, 0]], "type": "less", "target": 3},
+            {"indices": [[0, 1]], "type": "greater", "target": 0},
+            {"indices": [[1, 0]], "type": "unequal"}]}"#;
+        let imported = import(json, None).unwrap();
+        let rules: Vec<&str> = imported.puzzle.grid.iter().map(|e| e.rule.as_str()).collect();
+        assert_eq!(rules, ["<3", ">0", "!="]);
+        assert_eq!(imported.puzzle.grid[2].coords, vec![(1, 1)]);
+        assert!(imported.solution.is_none());
+        assert!(import(r#"{"dominoes": [], "regions": [{"indices": [], "type": "sum"}]}"#, None).unwrap_err().contains("target"));
+        assert!(import(r#"{"dominoes": [], "regions": [{"indices": [], "type": "mystery"}]}"#, None).is_err());
+        assert!(import(r#"{"grid": []}"#, None).is_err());
+    }
+}
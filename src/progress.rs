@@ -0,0 +1,120 @@
+//! Progress reports from long searches (`--progress`).
+//!
+//! Install a sink with `GameGrid::set_progress`; both backends report through it every `every` of search time
+//! (checked every 1024 nodes, like the time limit) and once more when the search ends. Not available on `wasm32`,
+//! which has no clock.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Receives progress reports as the search runs.
+pub type ProgressSink = Box<dyn FnMut(&Progress) + Send>;
+
+/// One level of the running search's decision path: choice `index` (0-based) of `count` at that depth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Branch {
+    pub index: usize,
+    pub count: usize,
+}
+
+/// A snapshot of the search.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    /// Search nodes visited so far.
+    pub nodes: u64,
+    /// Dominoes placed by the search (not counting any already on the board when it started).
+    pub depth: usize,
+    /// Estimated share of the search tree already explored, from the position of the decision path among the
+    /// choices at each level (as if every subtree were the same size). The first level dominates the estimate.
+    pub fraction: f64,
+    pub elapsed: Duration,
+    /// This is the last report of the search.
+    pub finished: bool,
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} nodes, depth {}, ~{:.1}% of the search tree, {:.1}s",
+            self.nodes, self.depth, 100.0 * self.fraction, self.elapsed.as_secs_f64())
+    }
+}
+
+/// Estimated share of the tree before the decision path `branches`.
+pub fn explored_fraction(branches: &[Branch]) -> f64 {
+    let mut fraction = 0.0;
+    let mut weight = 1.0;
+    for b in branches {
+        if b.count == 0 { break; }
+        let count = b.count as f64;
+        fraction += weight * b.index as f64 / count;
+        weight /= count;
+    }
+    fraction
+}
+
+/// Sink plus reporting schedule, held by the grid.
+pub(crate) struct Reporter {
+    sink: ProgressSink,
+    every: Duration,
+    started: Instant,
+    last: Instant,
+}
+
+impl Reporter {
+    pub(crate) fn new(sink: ProgressSink, every: Duration) -> Self {
+        let now = Instant::now();
+        Reporter { sink, every, started: now, last: now }
+    }
+
+    /// Start timing a new search.
+    pub(crate) fn restart(&mut self) {
+        self.started = Instant::now();
+        self.last = self.started;
+    }
+
+    /// Report if `every` has passed since the last report, or unconditionally when `finished`.
+    pub(crate) fn tick(&mut self, nodes: u64, branches: &[Branch], finished: bool) {
+        let now = Instant::now();
+        if !finished && now - self.last < self.every { return; }
+        self.last = now;
+        let fraction = if finished && branches.is_empty() { 1.0 } else { explored_fraction(branches) };
+        (self.sink)(&Progress { nodes, depth: branches.len(), fraction, elapsed: now - self.started, finished });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::GameGrid;
+    use crate::grid::test_support::open_board;
+    use crate::solver::{SOLVERS, SolveOptions};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn reports_during_and_after_the_search() {
+        assert_eq!(explored_fraction(&[Branch { index: 1, count: 4 }, Branch { index: 2, count: 4 }]), 0.375);
+        let file = open_board();
+        for solver in SOLVERS {
+            let reports = Arc::new(Mutex::new(Vec::new()));
+            let sink = reports.clone();
+            let mut g = GameGrid::from_parsed(file.clone());
+            g.set_progress(Some(Box::new(move |p| sink.lock().unwrap().push(*p))), Duration::ZERO);
+            let stats = solver.enumerate(&mut g, &SolveOptions { max_nodes: Some(5000), ..Default::default() }, &mut |_| false);
+            let reports = reports.lock().unwrap();
+            assert_eq!(reports.len() as u64, stats.nodes / 1024 + 1, "{}", solver.name());
+            assert!(reports.windows(2).all(|w| w[0].fraction <= w[1].fraction), "{reports:?}");
+            assert!(reports.iter().all(|p| p.depth <= 8 && p.fraction < 1.0));
+            let last = reports.last().unwrap();
+            assert!(last.finished && last.nodes == stats.nodes);
+
+            let mut g = GameGrid::from_parsed(serde_json::from_str(
+                r#"{"grid": [{"rule": "x", "coords": [[1,1],[2,1]]}], "dominoes": [[1,2]]}"#).unwrap());
+            let done = Arc::new(Mutex::new(None));
+            let sink = done.clone();
+            g.set_progress(Some(Box::new(move |p| *sink.lock().unwrap() = Some(*p))), Duration::from_secs(60));
+            solver.enumerate(&mut g, &SolveOptions::default(), &mut |_| false);
+            let done = done.lock().unwrap().unwrap();
+            assert_eq!((done.fraction, done.depth, done.finished), (1.0, 0, true), "an exhausted search explored everything");
+        }
+    }
+}
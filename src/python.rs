@@ -0,0 +1,133 @@
+//! Python extension module `pips_solver` (`--features python`).
+//!
+//! ```python
+//! from pips_solver import Puzzle
+//! puzzle = Puzzle.from_path("tests/grids/easy_grid.json")   # or Puzzle.from_dict(d) / Puzzle.from_json(s)
+//! puzzle.solve()          # [{"cells": [(1, 1), (2, 1)], "pips": (0, 0)}, ...] or None
+//! puzzle.count(limit=100) # number of solutions, stopping at `limit`
+//! puzzle.rate()           # {"nodes": 75, "unique": False, "score": 18.5, "label": "hard"} or None
+//! puzzle.svg()            # drawing of the first solution
+//! ```
+//! Placements use the puzzle's declared coordinate convention, as in `--json` output. Load and argument errors raise
+//! `ValueError`.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::grid::{GameGrid, GridFile, Placement};
+use crate::json_format;
+use crate::rating;
+use crate::solver::{self, SolveOptions, Solver};
+use crate::svg;
+
+fn value_error(message: impl ToString) -> PyErr { PyValueError::new_err(message.to_string()) }
+
+fn solver_named(name: Option<&str>) -> PyResult<&'static dyn Solver> {
+    match name {
+        Some(name) => solver::by_name(name).ok_or_else(|| value_error(format!("Unknown solver '{name}'."))),
+        None => Ok(solver::SOLVERS[0]),
+    }
+}
+
+fn placement_dicts<'py>(py: Python<'py>, placements: &[Placement]) -> PyResult<Vec<Bound<'py, PyDict>>> {
+    placements.iter().map(|p| {
+        let d = PyDict::new(py);
+        d.set_item("cells", p.cells.to_vec())?;
+        d.set_item("pips", (p.pips[0], p.pips[1]))?;
+        Ok(d)
+    }).collect()
+}
+
+/// A validated puzzle.
+#[pyclass(module = "pips_solver", frozen)]
+pub struct Puzzle {
+    file: GridFile,
+}
+
+#[pymethods]
+impl Puzzle {
+    /// Parse a JSON puzzle document.
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        GridFile::from_json(json).map(|file| Puzzle { file }).map_err(value_error)
+    }
+
+    /// Build from a dict shaped like the JSON format.
+    #[staticmethod]
+    fn from_dict(py: Python<'_>, data: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let json: String = py.import("json")?.call_method1("dumps", (data,))?.extract()?;
+        Self::from_json(&json)
+    }
+
+    /// Load a JSON or text-format (`.pips` / `.txt`) file.
+    #[staticmethod]
+    fn from_path(path: &str) -> PyResult<Self> {
+        GridFile::from_path(path).map(|file| Puzzle { file }).map_err(value_error)
+    }
+
+    /// Canonical JSON of the puzzle.
+    fn to_json(&self) -> String { json_format::write(&self.file) }
+
+    /// Placements of the first solution, or `None` if there is none.
+    #[pyo3(signature = (solver=None))]
+    fn solve<'py>(&self, py: Python<'py>, solver: Option<&str>) -> PyResult<Option<Vec<Bound<'py, PyDict>>>> {
+        let mut g = GameGrid::from_parsed(self.file.clone());
+        match solver_named(solver)?.solve(&mut g, &SolveOptions::default()).0 {
+            Some(_) => placement_dicts(py, &g.placements()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Placements of every solution, up to `limit`.
+    #[pyo3(signature = (limit=None, solver=None))]
+    fn solutions<'py>(&self, py: Python<'py>, limit: Option<usize>, solver: Option<&str>) -> PyResult<Vec<Vec<Bound<'py, PyDict>>>> {
+        let mut g = GameGrid::from_parsed(self.file.clone());
+        let mut found = Vec::new();
+        solver_named(solver)?.enumerate(&mut g, &SolveOptions { max_solutions: limit, ..Default::default() }, &mut |g| {
+            found.push(g.placements());
+            false
+        });
+        found.iter().map(|placements| placement_dicts(py, placements)).collect()
+    }
+
+    /// Number of solutions, stopping at `limit`.
+    #[pyo3(signature = (limit=None, solver=None))]
+    fn count(&self, limit: Option<usize>, solver: Option<&str>) -> PyResult<usize> {
+        let mut g = GameGrid::from_parsed(self.file.clone());
+        let opts = SolveOptions { max_solutions: limit, ..Default::default() };
+        Ok(solver_named(solver)?.enumerate(&mut g, &opts, &mut |_| false).solutions)
+    }
+
+    /// Difficulty rating (see `rating`), or `None` if the puzzle has no solution.
+    fn rate<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDict>>> {
+        let Some(r) = rating::rate(&mut GameGrid::from_parsed(self.file.clone())) else { return Ok(None) };
+        let d = PyDict::new(py);
+        d.set_item("nodes", r.nodes)?;
+        d.set_item("unique", r.unique)?;
+        d.set_item("score", r.score)?;
+        d.set_item("label", r.label)?;
+        Ok(Some(d))
+    }
+
+    /// SVG drawing of the board with `solution` (a list of placements), or with the first solution if omitted. An
+    /// unsolvable puzzle is drawn empty.
+    #[pyo3(signature = (solution=None))]
+    fn svg(&self, py: Python<'_>, solution: Option<&Bound<'_, PyAny>>) -> PyResult<String> {
+        let mut g = GameGrid::from_parsed(self.file.clone());
+        match solution {
+            Some(solution) => {
+                let json: String = py.import("json")?.call_method1("dumps", (solution,))?.extract()?;
+                let placements: Vec<Placement> = serde_json::from_str(&json).map_err(value_error)?;
+                g.apply_placements(&placements).map_err(value_error)?;
+            }
+            None => { g.solve(); }
+        }
+        Ok(svg::render(&g))
+    }
+}
+
+#[pymodule]
+fn pips_solver(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Puzzle>()
+}
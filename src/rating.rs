@@ -0,0 +1,56 @@
+//! Difficulty rating: how much trial and error a solver needs beyond forced moves.
+//!
+//! The puzzle is solved with the Dancing Links backend, which always branches on the most constrained cell, while
+//! also checking for a second solution. A puzzle that can be filled one forced placement after another visits one
+//! search node per domino beyond the root; every wrong guess adds more. The score is those nodes per domino.
+
+use crate::dlx::Dlx;
+use crate::grid::GameGrid;
+use crate::solver::{SolveOptions, Solver};
+
+/// Scores below this are "easy".
+const EASY_BELOW: f64 = 1.5;
+/// Scores below this (and not easy) are "medium"; the rest "hard".
+const MEDIUM_BELOW: f64 = 6.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rating {
+    /// Search nodes visited while finding the solution and looking for a second one.
+    pub nodes: u64,
+    /// Whether the solution is unique.
+    pub unique: bool,
+    /// Nodes below the root per domino; 1 when every placement is forced.
+    pub score: f64,
+    /// "easy", "medium" or "hard".
+    pub label: &'static str,
+}
+
+/// Rate `g`, or `None` if it has no solution. Leaves the board empty.
+pub fn rate(g: &mut GameGrid) -> Option<Rating> {
+    let stats = Dlx.enumerate(g, &SolveOptions { max_solutions: Some(2), ..Default::default() }, &mut |_| false);
+    g.reset();
+    if stats.solutions == 0 { return None; }
+    let score = stats.nodes.saturating_sub(1) as f64 / g.domino_inventory.len().max(1) as f64;
+    let label = if score < EASY_BELOW { "easy" } else if score < MEDIUM_BELOW { "medium" } else { "hard" };
+    Some(Rating { nodes: stats.nodes, unique: stats.solutions == 1, score, label })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::test_support::fixture;
+
+    fn rating(name: &str) -> Option<Rating> {
+        rate(&mut GameGrid::from_parsed(fixture(name)))
+    }
+
+    #[test]
+    fn forced_and_searching_puzzles() {
+        let mut forced = GameGrid::from_parsed(serde_json::from_str(
+            r#"{"grid": [{"rule": "=", "coords": [[1,1],[2,1]]}], "dominoes": [[2,2]]}"#).unwrap());
+        assert_eq!(rate(&mut forced), Some(Rating { nodes: 2, unique: true, score: 1.0, label: "easy" }));
+        let hard = rating("hard_grid.json").unwrap();
+        assert_eq!(hard.label, "hard", "{hard:?}");
+        assert!(rating("unsolvable_grid.json").is_none());
+    }
+}
@@ -0,0 +1,337 @@
+//! Minimal HTTP/1.1 service for solving puzzles without shelling out to the binary.
+//!
+//! Every endpoint takes a `POST` whose body is a puzzle document (a `GridFile`, optionally with a `solution` array as
+//! written by `--json`) and answers with one response per connection:
+//! - `/solve`: the solved document, as `--json` prints it.
+//! - `/verify`: `{"valid": true}`, or `{"valid": false, "error": ...}`, for the posted solution.
+//! - `/hint`: one more placement extending the posted (partial) solution, and how many are still missing.
+//! - `/count`: `{"solutions": N, "complete": bool, "nodes": N}`, stopping at `?limit=N` solutions (`limit=0` means no
+//!   limit). Running out of time is not an error here: the partial count comes back with `complete: false` instead
+//!   of a 504.
+//! - `/render?format=ascii|svg|json`: the posted board and solution drawn without solving.
+//!
+//! `?solver=<name>` picks the backend and `?timeout_ms=N` shortens the server's time limit for one request. Errors are
+//! `{"error": ...}` with a 4xx status, 503 when `MAX_CONNECTIONS` requests are already running, or 504 when a solve runs
+//! out of time. Overlap warnings about a posted puzzle go
+//! to the server's stderr.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use serde_json::json;
+
+use pips_solver::grid::GameGrid;
+use pips_solver::json_format::{self, SolvedFile};
+use pips_solver::solver::{self, SolveOptions, Solver};
+use pips_solver::svg;
+
+/// Largest request body accepted.
+const MAX_BODY: usize = 1 << 20;
+/// Largest request line plus headers accepted.
+const MAX_HEADER: usize = 16 << 10;
+/// Connections handled at once; further ones are turned away with 503 until one finishes.
+pub const MAX_CONNECTIONS: usize = 32;
+
+/// Server-wide settings.
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    /// Upper bound on the time any one request may spend searching.
+    pub timeout: Duration,
+}
+
+/// A parsed request: method, path without the query string, query parameters and body.
+#[derive(Debug, Default)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: HashMap<String, String>,
+    pub body: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl Response {
+    fn json(status: u16, value: serde_json::Value) -> Self {
+        Response { status, content_type: "application/json", body: format!("{value}\n") }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Response::json(status, json!({ "error": message.into() }))
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        422 => "Unprocessable Entity",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "Error",
+    }
+}
+
+/// Accept connections on `listener` forever, answering each on its own thread, at most `MAX_CONNECTIONS` at a time.
+pub fn serve(listener: TcpListener, config: Config) -> std::io::Result<()> {
+    let active = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let mut stream = stream?;
+        if active.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            active.fetch_sub(1, Ordering::SeqCst);
+            let _ = write_response(&mut stream, &Response::error(503, "Too many requests in progress; try again."));
+            continue;
+        }
+        let active = active.clone();
+        std::thread::spawn(move || {
+            // A client that hangs up early is not the server's problem.
+            let _ = handle_connection(stream, config);
+            active.fetch_sub(1, Ordering::SeqCst);
+        });
+    }
+    Ok(())
+}
+
+fn handle_connection(mut stream: TcpStream, config: Config) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    let response = match read_request(&mut BufReader::new(&mut stream)) {
+        Ok(request) => handle(&request, &config),
+        Err(response) => response,
+    };
+    write_response(&mut stream, &response)
+}
+
+fn write_response(stream: &mut TcpStream, response: &Response) -> std::io::Result<()> {
+    write!(stream, "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status, reason(response.status), response.content_type, response.body.len(), response.body)?;
+    stream.flush()
+}
+
+/// Read the request line, headers (`MAX_HEADER` bytes in all) and a `Content-Length` body.
+fn read_request(reader: &mut impl BufRead) -> Result<Request, Response> {
+    let bad = |e: std::io::Error| Response::error(400, e.to_string());
+    let mut budget = MAX_HEADER;
+    let mut read_head_line = |reader: &mut _, line: &mut String| -> Result<usize, Response> {
+        line.clear();
+        let n = Read::take(reader, budget as u64).read_line(line).map_err(bad)?;
+        budget -= n;
+        if budget == 0 && !line.ends_with('\n') {
+            return Err(Response::error(431, format!("Request line and headers over {MAX_HEADER} bytes.")));
+        }
+        Ok(n)
+    };
+    let mut line = String::new();
+    read_head_line(&mut *reader, &mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(Response::error(400, "Malformed request line."));
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = query.split('&').filter(|p| !p.is_empty())
+        .map(|p| { let (k, v) = p.split_once('=').unwrap_or((p, "")); (k.to_string(), v.to_string()) })
+        .collect();
+    let mut length = 0usize;
+    let mut header = String::new();
+    loop {
+        if read_head_line(&mut *reader, &mut header)? == 0 || header.trim().is_empty() { break; }
+        if let Some((name, value)) = header.split_once(':')
+            && name.trim().eq_ignore_ascii_case("content-length") {
+            length = value.trim().parse().map_err(|_| Response::error(400, "Invalid Content-Length."))?;
+        }
+    }
+    if length > MAX_BODY { return Err(Response::error(413, format!("Request body over {MAX_BODY} bytes."))); }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).map_err(bad)?;
+    let body = String::from_utf8(body).map_err(|_| Response::error(400, "Request body is not UTF-8."))?;
+    Ok(Request { method: method.to_string(), path: path.to_string(), query, body })
+}
+
+/// Answer one request.
+pub fn handle(request: &Request, config: &Config) -> Response {
+    let endpoint: fn(&Request, SolvedFile, Settings) -> Response = match request.path.as_str() {
+        "/solve" => solve,
+        "/verify" => verify,
+        "/hint" => hint,
+        "/count" => count,
+        "/render" => render,
+        _ => return Response::error(404, format!("No endpoint {}.", request.path)),
+    };
+    if request.method != "POST" { return Response::error(405, "Use POST with a puzzle document as the body."); }
+    let settings = match Settings::new(request, config) {
+        Ok(settings) => settings,
+        Err(message) => return Response::error(400, message),
+    };
+    match json_format::read_solved(&request.body) {
+        Ok(document) => {
+            crate::warn_overlaps(&document.puzzle, Some(&request.path));
+            endpoint(request, document, settings)
+        }
+        Err(message) => Response::error(400, message),
+    }
+}
+
+/// Per-request search settings taken from the query string.
+#[derive(Clone, Copy)]
+struct Settings {
+    solver: &'static dyn Solver,
+    timeout: Duration,
+}
+
+impl Settings {
+    fn new(request: &Request, config: &Config) -> Result<Self, String> {
+        let solver = match request.query.get("solver") {
+            Some(name) => solver::by_name(name).ok_or_else(|| format!("Unknown solver '{name}'."))?,
+            None => solver::SOLVERS[0],
+        };
+        let timeout = match request.query.get("timeout_ms") {
+            Some(ms) => Duration::from_millis(ms.parse().map_err(|_| format!("Invalid timeout_ms: {ms}"))?).min(config.timeout),
+            None => config.timeout,
+        };
+        Ok(Settings { solver, timeout })
+    }
+
+    fn options(&self, max_solutions: Option<usize>) -> SolveOptions {
+        SolveOptions { max_solutions, timeout: Some(self.timeout), ..Default::default() }
+    }
+
+    fn timed_out(&self) -> Response {
+        Response::error(504, format!("Gave up after {} ms.", self.timeout.as_millis()))
+    }
+}
+
+fn solve(_: &Request, document: SolvedFile, settings: Settings) -> Response {
+    let mut g = GameGrid::from_parsed(document.puzzle.clone());
+    let (found, stats) = settings.solver.solve(&mut g, &settings.options(None));
+    match found {
+        Some(_) => Response { status: 200, content_type: "application/json", body: json_format::write_solved(&document.puzzle, &g.placements()) },
+        None if stats.timed_out => settings.timed_out(),
+        None => Response::error(422, "No solution found."),
+    }
+}
+
+fn verify(_: &Request, document: SolvedFile, _: Settings) -> Response {
+    let mut g = GameGrid::from_parsed(document.puzzle);
+    match g.verify_placements(&document.solution) {
+        Ok(()) => Response::json(200, json!({ "valid": true })),
+        Err(message) => Response::json(200, json!({ "valid": false, "error": message })),
+    }
+}
+
+fn hint(_: &Request, document: SolvedFile, settings: Settings) -> Response {
+    let mut g = GameGrid::from_parsed(document.puzzle);
+    if let Err(message) = g.apply_placements(&document.solution) { return Response::error(422, message); }
+    let stats = settings.solver.enumerate_from(&mut g, &settings.options(Some(1)), &mut |_| true);
+    if stats.timed_out { return settings.timed_out(); }
+    if stats.solutions == 0 { return Response::error(422, "No solution extends the given placements."); }
+    // Posted placements may list their cells in either order, so match them by the cells they cover.
+    let given: Vec<_> = document.solution.iter().flat_map(|p| p.cells).collect();
+    let missing: Vec<_> = g.placements().into_iter().filter(|p| !given.contains(&p.cells[0])).collect();
+    Response::json(200, json!({ "hint": missing.first(), "remaining": missing.len() }))
+}
+
+fn count(request: &Request, document: SolvedFile, settings: Settings) -> Response {
+    let limit = match request.query.get("limit").map(|n| n.parse::<usize>()) {
+        Some(Ok(0)) => None,
+        Some(Ok(n)) => Some(n),
+        Some(Err(_)) => return Response::error(400, "Invalid limit."),
+        None => None,
+    };
+    let mut g = GameGrid::from_parsed(document.puzzle);
+    let stats = settings.solver.enumerate(&mut g, &settings.options(limit), &mut |_| false);
+    let complete = !stats.aborted && limit.is_none_or(|n| stats.solutions < n);
+    Response::json(200, json!({ "solutions": stats.solutions, "complete": complete, "nodes": stats.nodes }))
+}
+
+fn render(request: &Request, document: SolvedFile, _: Settings) -> Response {
+    let mut g = GameGrid::from_parsed(document.puzzle.clone());
+    if let Err(message) = g.apply_placements(&document.solution) { return Response::error(422, message); }
+    match request.query.get("format").map_or("ascii", String::as_str) {
+        "ascii" => Response { status: 200, content_type: "text/plain; charset=utf-8", body: g.ascii_board(false) },
+        "svg" => Response { status: 200, content_type: "image/svg+xml", body: svg::render(&g) },
+        "json" => Response { status: 200, content_type: "application/json", body: json_format::write_solved(&document.puzzle, &g.placements()) },
+        other => Response::error(400, format!("Unknown format '{other}' (expected one of: ascii, svg, json).")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str = r#"{"grid": [{"rule": "=", "coords": [[1,1],[2,1]]}, {"rule": "7", "coords": [[1,2],[2,2]]}],
+        "dominoes": [[3,4],[2,2]]}"#;
+
+    fn post(path: &str, body: &str) -> Response {
+        let (path, query) = path.split_once('?').unwrap_or((path, ""));
+        let query = query.split('&').filter_map(|p| p.split_once('=')).map(|(k, v)| (k.into(), v.into())).collect();
+        let request = Request { method: "POST".into(), path: path.into(), query, body: body.into() };
+        handle(&request, &Config { timeout: Duration::from_secs(5) })
+    }
+
+    #[test]
+    fn solve_hint_and_verify() {
+        let solved = post("/solve?solver=dlx", PUZZLE);
+        assert_eq!(solved.status, 200, "{}", solved.body);
+        assert_eq!(post("/verify", &solved.body).body, "{\"valid\":true}\n");
+        let unsolved = post("/verify", PUZZLE);
+        assert!(unsolved.body.contains("\"valid\":false"));
+
+        let hint: serde_json::Value = serde_json::from_str(&post("/hint", PUZZLE).body).unwrap();
+        assert_eq!(hint["remaining"], 2);
+        let partial = PUZZLE.replace("]]}", "]], \"solution\": [{\"cells\": [[1,1],[2,1]], \"pips\": [2,2]}]}");
+        let hint: serde_json::Value = serde_json::from_str(&post("/hint", &partial).body).unwrap();
+        assert_eq!(hint["remaining"], 1);
+        assert_eq!(hint["hint"]["cells"], json!([[1, 2], [2, 2]]));
+        let reversed = PUZZLE.replace("]]}", "]], \"solution\": [{\"cells\": [[2,1],[1,1]], \"pips\": [2,2]}]}");
+        let hint: serde_json::Value = serde_json::from_str(&post("/hint", &reversed).body).unwrap();
+        assert_eq!((hint["remaining"].as_u64(), &hint["hint"]["cells"]), (Some(1), &json!([[1, 2], [2, 2]])));
+        let wrong = PUZZLE.replace("]]}", "]], \"solution\": [{\"cells\": [[1,2],[2,2]], \"pips\": [2,2]}]}");
+        assert_eq!(post("/hint", &wrong).status, 422);
+    }
+
+    #[test]
+    fn count_render_and_errors() {
+        let counted: serde_json::Value = serde_json::from_str(&post("/count", PUZZLE).body).unwrap();
+        assert_eq!((counted["solutions"].as_u64(), counted["complete"].as_bool()), (Some(2), Some(true)));
+        let limited: serde_json::Value = serde_json::from_str(&post("/count?limit=1", PUZZLE).body).unwrap();
+        assert_eq!(limited["complete"], false);
+        let unlimited: serde_json::Value = serde_json::from_str(&post("/count?limit=0", PUZZLE).body).unwrap();
+        assert_eq!((unlimited["solutions"].as_u64(), unlimited["complete"].as_bool()), (Some(2), Some(true)));
+
+        let svg = post("/render?format=svg", PUZZLE);
+        assert_eq!((svg.status, svg.content_type), (200, "image/svg+xml"));
+        assert_eq!(post("/render?format=png", PUZZLE).status, 400);
+
+        assert_eq!(post("/nope", PUZZLE).status, 404);
+        assert_eq!(post("/solve?solver=magic", PUZZLE).status, 400);
+        assert_eq!(post("/solve", "{").status, 400);
+        assert_eq!(post("/solve", r#"{"grid": [{"rule": "=", "coords": [[1,1],[2,1]]}], "dominoes": [[1,2]]}"#).status, 422);
+        let get = handle(&Request { method: "GET".into(), path: "/solve".into(), ..Default::default() }, &Config { timeout: Duration::from_secs(1) });
+        assert_eq!(get.status, 405);
+    }
+
+    #[test]
+    fn reads_headers_and_body() {
+        let raw = "POST /count?limit=3&solver=dlx HTTP/1.1\r\nHost: x\r\ncontent-length: 4\r\n\r\nbody";
+        let request = read_request(&mut raw.as_bytes()).unwrap();
+        assert_eq!((request.method.as_str(), request.path.as_str(), request.body.as_str()), ("POST", "/count", "body"));
+        assert_eq!(request.query["limit"], "3");
+        let huge = format!("POST /solve HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_BODY + 1);
+        assert_eq!(read_request(&mut huge.as_bytes()).unwrap_err().status, 413);
+        let long = format!("POST /solve HTTP/1.1\r\nX-Padding: {}\r\n\r\n", "a".repeat(MAX_HEADER));
+        assert_eq!(read_request(&mut long.as_bytes()).unwrap_err().status, 431);
+        let endless = "X-Header: a\r\n".repeat(MAX_HEADER);
+        assert_eq!(read_request(&mut format!("POST /solve HTTP/1.1\r\n{endless}").as_bytes()).unwrap_err().status, 431);
+    }
+}
@@ -0,0 +1,199 @@
+//! Search backends behind a common interface, selectable by name (`--solver <name>`).
+//!
+//! A backend only provides the raw search; resetting the board, the node and time budgets, solution limits and
+//! statistics are handled once by the provided `solve` / `enumerate` methods.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::dlx::Dlx;
+use crate::grid::{Coord, GameGrid};
+
+/// Limits for a solve or enumeration. `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SolveOptions {
+    /// Stop after this many solutions.
+    pub max_solutions: Option<usize>,
+    /// Give up after visiting this many search nodes.
+    pub max_nodes: Option<u64>,
+    /// Give up once this much time has passed (not supported on `wasm32`).
+    pub timeout: Option<Duration>,
+}
+
+/// What a search did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SolveStats {
+    pub nodes: u64,
+    pub solutions: usize,
+    /// The node budget or the time limit ran out, or the stop flag was set, before the search finished.
+    pub aborted: bool,
+    /// It was the time limit.
+    pub timed_out: bool,
+    /// It was the stop flag (`GameGrid::set_stop_flag`).
+    pub interrupted: bool,
+}
+
+pub trait Solver: Sync {
+    /// Name used on the command line.
+    fn name(&self) -> &'static str;
+
+    /// Search the board from its current state. `on_solution` is called with `g` holding each complete solution and
+    /// returns `true` to stop, leaving that solution on the board. Returns whether the search stopped early; it must
+    /// also stop as soon as `GameGrid::visit_node` reports the budget spent.
+    fn search(&self, g: &mut GameGrid, on_solution: &mut dyn FnMut(&GameGrid) -> bool) -> bool;
+
+    /// Visit solutions from an empty board until `on_solution` returns `true`, a limit is hit or the search space is
+    /// exhausted. Afterwards the board holds the last accepted solution, or is empty.
+    fn enumerate(&self, g: &mut GameGrid, opts: &SolveOptions, on_solution: &mut dyn FnMut(&GameGrid) -> bool) -> SolveStats {
+        g.reset();
+        self.enumerate_from(g, opts, on_solution)
+    }
+
+    /// Like `enumerate`, but only visits solutions extending the dominoes already on the board. If a limit is hit the
+    /// board is put back as it was.
+    fn enumerate_from(&self, g: &mut GameGrid, opts: &SolveOptions, on_solution: &mut dyn FnMut(&GameGrid) -> bool) -> SolveStats {
+        let start = g.placements();
+        g.nodes = 0;
+        g.node_limit = opts.max_nodes.unwrap_or(u64::MAX);
+        g.deadline = opts.timeout.and_then(deadline_after);
+        g.timed_out = false;
+        g.interrupted = false;
+        g.branches.clear();
+        if let Some(progress) = &mut g.progress { progress.restart(); }
+        let mut solutions = 0;
+        self.search(g, &mut |g| {
+            solutions += 1;
+            on_solution(g) || opts.max_solutions.is_some_and(|max| solutions >= max)
+        });
+        let (timed_out, interrupted) = (g.timed_out, g.interrupted);
+        let aborted = timed_out || interrupted || g.nodes > g.node_limit;
+        if let Some(progress) = &mut g.progress { progress.tick(g.nodes, &g.branches, true); }
+        g.resume.clear();
+        g.node_limit = u64::MAX;
+        g.deadline = None;
+        g.timed_out = false;
+        g.interrupted = false;
+        if aborted { g.apply_placements(&start).expect("placements were on this board"); }
+        SolveStats { nodes: g.nodes, solutions, aborted, timed_out, interrupted }
+    }
+
+    /// Find the first solution, leaving it on the board. Coordinates are in the puzzle file's declared convention.
+    fn solve(&self, g: &mut GameGrid, opts: &SolveOptions) -> (Option<HashMap<Coord, u8>>, SolveStats) {
+        let stats = self.enumerate(g, &SolveOptions { max_solutions: Some(1), ..*opts }, &mut |_| true);
+        ((stats.solutions > 0).then(|| g.assignment()), stats)
+    }
+}
+
+/// When a search given `timeout` must stop. `wasm32-unknown-unknown` has no clock, so time limits are ignored there.
+fn deadline_after(timeout: Duration) -> Option<Instant> {
+    if cfg!(target_arch = "wasm32") { None } else { Some(Instant::now() + timeout) }
+}
+
+/// Depth-first search filling the first empty cell, trying dominoes in inventory order (the original algorithm).
+pub struct Backtracking;
+
+impl Solver for Backtracking {
+    fn name(&self) -> &'static str { "backtrack" }
+
+    fn search(&self, g: &mut GameGrid, on_solution: &mut dyn FnMut(&GameGrid) -> bool) -> bool {
+        g.backtrack(on_solution)
+    }
+}
+
+/// Every available backend; the first is the default.
+pub const SOLVERS: [&dyn Solver; 2] = [&Backtracking, &Dlx];
+
+pub fn by_name(name: &str) -> Option<&'static dyn Solver> {
+    SOLVERS.into_iter().find(|s| s.name() == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{GridFile, Placement};
+    use crate::grid::test_support::{FIXTURES, open_board};
+    use crate::text_format;
+
+    /// Every fixture in `tests/grids`, JSON or text format.
+    fn fixtures() -> Vec<String> {
+        let mut paths: Vec<String> = std::fs::read_dir(FIXTURES).unwrap()
+            .map(|e| e.unwrap().path().to_string_lossy().into_owned())
+            .filter(|p| p.ends_with(".json") || p.ends_with(&format!(".{}", text_format::EXTENSION)))
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn backends_agree_on_every_fixture() {
+        for path in fixtures() {
+            let mut expected: Option<(bool, usize)> = None;
+            for solver in SOLVERS {
+                let mut g = GameGrid::from_file(&path).unwrap();
+                let (found, stats) = solver.solve(&mut g, &SolveOptions::default());
+                if let Some(pips) = &found {
+                    assert!(g.check_assignment(pips), "{path}: {} returned an invalid solution", solver.name());
+                    assert_eq!(g.placements().len() * 2, pips.len(), "{path}: every cell covered by a placed domino");
+                }
+                assert!(!stats.aborted);
+                let mut count = 0;
+                let all = solver.enumerate(&mut g, &SolveOptions::default(), &mut |g| {
+                    let mut check = GameGrid::from_parsed(GridFile { grid: g.entries.clone(), ..Default::default() });
+                    assert!(check.check_assignment(&g.occupied), "{path}: {} enumerated an invalid solution", solver.name());
+                    count += 1;
+                    false
+                });
+                assert_eq!(all.solutions, count);
+                let result = (found.is_some(), count);
+                match expected {
+                    None => expected = Some(result),
+                    Some(e) => assert_eq!(result, e, "{path}: {} disagrees with {}", solver.name(), SOLVERS[0].name()),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn limits_and_stats() {
+        // 2x2 board, unconstrained, two distinct dominoes: 16 solutions.
+        let file: GridFile = serde_json::from_str(
+            r#"{"grid": [{"rule": "x", "coords": [[1,1],[2,1],[1,2],[2,2]]}], "dominoes": [[1,2],[3,4]]}"#).unwrap();
+        for solver in SOLVERS {
+            let mut g = GameGrid::from_parsed(file.clone());
+            let all = solver.enumerate(&mut g, &SolveOptions::default(), &mut |_| false);
+            assert_eq!((all.solutions, all.aborted), (16, false), "{}", solver.name());
+            assert!(g.occupied.is_empty());
+            let some = solver.enumerate(&mut g, &SolveOptions { max_solutions: Some(3), ..Default::default() }, &mut |_| false);
+            assert_eq!(some.solutions, 3);
+            assert_eq!(g.occupied.len(), 4, "the last accepted solution stays on the board");
+            let starved = solver.solve(&mut g, &SolveOptions { max_nodes: Some(1), ..Default::default() });
+            assert!(starved.0.is_none() && starved.1.aborted, "{}", solver.name());
+            assert!(g.occupied.is_empty());
+        }
+        assert_eq!(by_name("dlx").map(|s| s.name()), Some("dlx"));
+        assert!(by_name("nope").is_none());
+    }
+
+    #[test]
+    fn time_limit_and_partial_boards() {
+        let file = open_board();
+        let small: GridFile = serde_json::from_str(
+            r#"{"grid": [{"rule": "x", "coords": [[1,1],[2,1],[1,2],[2,2]]}], "dominoes": [[1,2],[3,4]]}"#).unwrap();
+        for solver in SOLVERS {
+            let mut g = GameGrid::from_parsed(file.clone());
+            let late = solver.enumerate(&mut g, &SolveOptions { timeout: Some(Duration::ZERO), ..Default::default() }, &mut |_| false);
+            assert!(late.aborted && late.timed_out, "{}", solver.name());
+            assert_eq!(late.nodes, 1024);
+            assert!(g.occupied.is_empty());
+
+            let mut g = GameGrid::from_parsed(small.clone());
+            let fixed = [Placement { cells: [(1, 1), (2, 1)], pips: [1, 2] }];
+            g.apply_placements(&fixed).unwrap();
+            let rest = solver.enumerate_from(&mut g, &SolveOptions::default(), &mut |_| false);
+            assert_eq!(rest.solutions, 2, "{}: the 3-4 domino either way round on the top row", solver.name());
+            let starved = solver.enumerate_from(&mut g, &SolveOptions { max_nodes: Some(1), ..Default::default() }, &mut |_| false);
+            assert!(starved.aborted && !starved.timed_out);
+            assert_eq!(g.placements(), fixed, "the starting placements are restored");
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! SVG rendering of a board and its current assignment.
+//!
+//! Cells are drawn as squares, or as pointy-top hexagons on a hex topology. Empty cells are tinted by region (white
+//! outside every region, dashed when shared by several), covered cells take their domino's colour with a bar joining
+//! the two halves, and each region's rule is written in the corner of its first cell.
+
+use std::fmt::Write;
+
+use crate::grid::{Coord, GameGrid, Placement, Rule};
+use crate::topology::Topology;
+
+/// Distance between neighbouring cell centres in a row, in SVG user units.
+const PITCH: f64 = 48.0;
+const MARGIN: f64 = 4.0;
+
+const DOMINO_FILLS: [&str; 12] = [
+    "#f8a5a5", "#f9c58d", "#f7ef8a", "#a8e6a1", "#9fe3e6", "#a3bdf5",
+    "#cfa8f0", "#f5a8dc", "#f6b77c", "#c6f08a", "#b9a8f0", "#d0d0d0",
+];
+const REGION_TINTS: [&str; 6] = ["#f4f4f4", "#e9eef7", "#f7efe6", "#eaf5ea", "#f5eaf3", "#f2f2e4"];
+
+/// Escape text for use inside an SVG element.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Board geometry: where cell centres go and how cells are outlined.
+struct Layout {
+    min_x: u32,
+    max_y: u32,
+    hex: bool,
+}
+
+impl Layout {
+    fn new(g: &GameGrid) -> Self {
+        let min_x = g.ordered_coords.iter().map(|c| c.0).min().unwrap_or(0);
+        let max_y = g.ordered_coords.iter().map(|c| c.1).max().unwrap_or(0);
+        Layout { min_x, max_y, hex: g.topology == Topology::Hex }
+    }
+
+    fn row_pitch(&self) -> f64 { if self.hex { PITCH * 3f64.sqrt() / 2.0 } else { PITCH } }
+
+    fn centre(&self, g: &GameGrid, (x, y): Coord) -> (f64, f64) {
+        let shift = if g.topology.row_shifted(y) { PITCH / 2.0 } else { 0.0 };
+        let cx = MARGIN + (x - self.min_x) as f64 * PITCH + shift + PITCH / 2.0;
+        let cy = MARGIN + (self.max_y - y) as f64 * self.row_pitch() + self.cell_height() / 2.0;
+        (cx, cy)
+    }
+
+    fn cell_height(&self) -> f64 { if self.hex { PITCH * 2.0 / 3f64.sqrt() } else { PITCH } }
+
+    fn outline(&self, (cx, cy): (f64, f64)) -> String {
+        if self.hex {
+            let r = self.cell_height() / 2.0 - 1.0;
+            let points: Vec<String> = (0..6).map(|k| {
+                let a = (60.0 * k as f64 + 30.0).to_radians();
+                format!("{:.1},{:.1}", cx + r * a.cos(), cy + r * a.sin())
+            }).collect();
+            format!("<polygon points=\"{}\"", points.join(" "))
+        } else {
+            let half = PITCH / 2.0 - 1.0;
+            format!("<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\"", cx - half, cy - half, 2.0 * half, 2.0 * half)
+        }
+    }
+}
+
+/// Render `g` with its current assignment (which may be empty or partial).
+pub fn render(g: &GameGrid) -> String {
+    let layout = Layout::new(g);
+    let mut out = open_svg(g, &layout);
+    draw_board(g, &layout, &mut out);
+    out.push_str("</svg>\n");
+    out
+}
+
+/// Render `frames` (see `animate::frames`) as one SVG that shows each for `delay_ms` and then stays on the last.
+/// Leaves the last frame on `g`.
+pub fn render_animation(g: &mut GameGrid, frames: &[Vec<Placement>], delay_ms: u64) -> Result<String, String> {
+    let layout = Layout::new(g);
+    let mut out = open_svg(g, &layout);
+    for (i, frame) in frames.iter().enumerate() {
+        g.apply_placements(frame)?;
+        // Every frame starts hidden so nothing flashes before its turn; only the last one stays.
+        writeln!(out, "<g visibility=\"hidden\">").ok();
+        writeln!(out, "  <set attributeName=\"visibility\" to=\"visible\" begin=\"{}ms\"/>", (i as u64).saturating_mul(delay_ms)).ok();
+        if i + 1 < frames.len() {
+            writeln!(out, "  <set attributeName=\"visibility\" to=\"hidden\" begin=\"{}ms\"/>", (i as u64 + 1).saturating_mul(delay_ms)).ok();
+        }
+        draw_board(g, &layout, &mut out);
+        out.push_str("</g>\n");
+    }
+    out.push_str("</svg>\n");
+    Ok(out)
+}
+
+/// The opening `<svg>` tag, sized to the board.
+fn open_svg(g: &GameGrid, layout: &Layout) -> String {
+    let max_x = g.ordered_coords.iter().map(|c| c.0).max().unwrap_or(0);
+    let min_y = g.ordered_coords.iter().map(|c| c.1).min().unwrap_or(0);
+    let columns = if g.ordered_coords.is_empty() { 0.0 } else { (max_x - layout.min_x + 1) as f64 + if layout.hex { 0.5 } else { 0.0 } };
+    let rows = if g.ordered_coords.is_empty() { 0 } else { layout.max_y - min_y + 1 };
+    let width = 2.0 * MARGIN + columns * PITCH;
+    let height = 2.0 * MARGIN + if rows == 0 { 0.0 } else { (rows - 1) as f64 * layout.row_pitch() + layout.cell_height() };
+    let mut out = String::new();
+    writeln!(out, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width:.0}\" height=\"{height:.0}\" viewBox=\"0 0 {width:.1} {height:.1}\" font-family=\"sans-serif\">").ok();
+    out
+}
+
+/// Cells, domino bars, pips and rule labels.
+fn draw_board(g: &GameGrid, layout: &Layout, out: &mut String) {
+    for &c in &g.ordered_coords {
+        let regions: Vec<usize> = (0..g.entries.len()).filter(|&i| g.entries[i].coords.contains(&c)).collect();
+        let fill = match (g.domino_at(c), regions.first()) {
+            (Some(id), _) => DOMINO_FILLS[id % DOMINO_FILLS.len()],
+            (None, Some(&region)) => REGION_TINTS[region % REGION_TINTS.len()],
+            (None, None) => "#ffffff",
+        };
+        // Cells shared by several regions get a dashed outline.
+        let dash = if regions.len() > 1 { " stroke-dasharray=\"4 3\"" } else { "" };
+        writeln!(out, "  {} fill=\"{fill}\" stroke=\"#444\" stroke-width=\"1\"{dash}/>", layout.outline(layout.centre(g, c))).ok();
+    }
+
+    // Bars between domino halves that are drawn next to each other (wrap-around neighbours on a torus are not).
+    for &a in &g.ordered_coords {
+        let Some(id) = g.domino_at(a) else { continue };
+        for &b in g.neighbors(a) {
+            if b <= a || g.domino_at(b) != Some(id) { continue; }
+            let ((x1, y1), (x2, y2)) = (layout.centre(g, a), layout.centre(g, b));
+            if (x1 - x2).hypot(y1 - y2) > PITCH * 1.01 { continue; }
+            writeln!(out, "  <line x1=\"{x1:.1}\" y1=\"{y1:.1}\" x2=\"{x2:.1}\" y2=\"{y2:.1}\" stroke=\"#0005\" stroke-width=\"6\" stroke-linecap=\"round\"/>").ok();
+        }
+    }
+
+    for &c in &g.ordered_coords {
+        let Some(v) = g.occupied.get(&c) else { continue };
+        let (cx, cy) = layout.centre(g, c);
+        writeln!(out, "  <text x=\"{cx:.1}\" y=\"{:.1}\" font-size=\"20\" text-anchor=\"middle\">{v}</text>", cy + 7.0).ok();
+    }
+
+    for (entry, rule) in g.entries.iter().zip(&g.parsed_rules) {
+        // Label the top-left cell of the region as drawn.
+        let Some(&first) = entry.coords.iter().min_by_key(|c| (std::cmp::Reverse(c.1), c.0)) else { continue };
+        let label = if *rule == Rule::Unknown { entry.rule.clone() } else { rule.to_string() };
+        let (cx, cy) = layout.centre(g, first);
+        writeln!(out, "  <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"10\" fill=\"#333\">{}</text>",
+            cx - PITCH / 2.0 + 5.0, cy - layout.cell_height() / 2.0 + 13.0, escape(&label)).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::GridFile;
+
+    fn grid(json: &str) -> GameGrid { GameGrid::from_parsed(serde_json::from_str::<GridFile>(json).unwrap()) }
+
+    #[test]
+    fn square_board_with_solution() {
+        let mut g = grid(r#"{"grid": [{"rule": "<9", "coords": [[1,1],[2,1]]}], "dominoes": [[3,4]]}"#);
+        g.solve().unwrap();
+        let svg = render(&g);
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<rect").count(), 2);
+        assert_eq!(svg.matches("<line").count(), 1);
+        assert!(svg.contains(">3</text>") && svg.contains(">4</text>"));
+        assert!(svg.contains("&lt;9"), "rule labels are escaped");
+        assert!(svg.contains("width=\"104\""));
+    }
+
+    #[test]
+    fn shared_and_free_cells() {
+        let g = grid(r#"{"grid": [{"rule": "5", "coords": [[1,1],[2,1]]}, {"rule": "odd", "coords": [[2,1]], "overlap": true}],
+            "cells": [[3,1]], "dominoes": []}"#);
+        let svg = render(&g);
+        assert_eq!(svg.matches("<rect").count(), 3);
+        assert_eq!(svg.matches("stroke-dasharray").count(), 1);
+        assert!(svg.contains("fill=\"#ffffff\""));
+        assert!(svg.contains(">5</text>") && svg.contains(">odd</text>"));
+    }
+
+    #[test]
+    fn animation_shows_each_frame_in_turn() {
+        let mut g = grid(r#"{"grid": [{"rule": "x", "coords": [[1,1],[2,1],[1,2],[2,2]]}], "dominoes": [[1,2],[3,4]]}"#);
+        let frames = crate::animate::frames(&mut g, &crate::solver::Backtracking, crate::animate::AnimateMode::Solution).unwrap();
+        let svg = render_animation(&mut g, &frames, 250).unwrap();
+        assert_eq!(svg.matches("<svg").count(), 1);
+        assert_eq!(svg.matches("<g visibility=\"hidden\">").count(), 3);
+        assert_eq!(svg.matches("<rect").count(), 12);
+        assert_eq!(svg.matches("<line").count(), 3, "one bar in the middle frame, two in the last");
+        assert!(svg.contains("to=\"visible\" begin=\"500ms\""));
+        assert_eq!(svg.matches("to=\"hidden\"").count(), 2, "the last frame stays");
+        let slow = render_animation(&mut g, &frames, u64::MAX).unwrap();
+        assert!(slow.contains(&format!("begin=\"{}ms\"", u64::MAX)), "huge delays saturate instead of overflowing");
+    }
+
+    #[test]
+    fn hex_board_draws_hexagons_in_offset_rows() {
+        let g = grid(r#"{"topology": {"kind": "hex"}, "grid": [{"rule": "x", "coords": [[1,1],[1,2]]}], "dominoes": [[1,2]]}"#);
+        let svg = render(&g);
+        assert_eq!(svg.matches("<polygon").count(), 2);
+        assert!(!svg.contains("<line"), "nothing placed yet");
+        let layout = Layout::new(&g);
+        let (bottom, top) = (layout.centre(&g, (1, 1)), layout.centre(&g, (1, 2)));
+        assert!((bottom.0 - top.0 - PITCH / 2.0).abs() < 1e-9, "odd rows are shifted right by half a cell");
+    }
+}
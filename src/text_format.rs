@@ -0,0 +1,341 @@
+//! Compact plain-text puzzle format.
+//!
+//! ```text
+//! # comments start with '#'
+//! board:
+//! ABB
+//! CCC
+//! DD.
+//! rules:
+//! A x
+//! B 1
+//! C =
+//! D 4
+//! dominoes:
+//! 0-0 3-1 3-3 4-0
+//! ```
+//! The board is drawn top row first; each letter or digit names a region, `+` is a cell outside every region and `.`
+//! (or a space) is a hole. Regions cannot overlap in a drawing; overlapping regions need JSON.
+//! Before the first section, `max-pip: 9` declares a larger domino set, `not-equal: not-all-equal` selects the
+//! legacy meaning of `!=` (see `NotEqualMode`) and `topology: hex` or `topology: torus 4x3` changes adjacency (see
+//! `topology`; explicit adjacency lists need JSON).
+//! Coordinates follow the JSON convention: the bottom-left cell of the drawing is `(1,1)`.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::grid::{Coord, Domino, GridEntry, GridFile, NotEqualMode, canonical_rule};
+use crate::topology::Topology;
+
+/// The text format's own extension, the only one picked up when scanning a directory.
+pub const EXTENSION: &str = "pips";
+
+/// Whether a file named explicitly should be read as the text format: `.pips`, or `.txt`.
+pub fn is_text_path(path: &str) -> bool {
+    std::path::Path::new(path)
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case(EXTENSION) || e.eq_ignore_ascii_case("txt"))
+}
+
+/// Parse failure with the 1-based line it occurred on (0 when not tied to a line).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextFormatError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TextFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 { write!(f, "{}", self.message) } else { write!(f, "line {}: {}", self.line, self.message) }
+    }
+}
+
+impl std::error::Error for TextFormatError {}
+
+fn err<T>(line: usize, message: impl Into<String>) -> Result<T, TextFormatError> {
+    Err(TextFormatError { line, message: message.into() })
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Section {
+    None,
+    Board,
+    Rules,
+    Dominoes,
+}
+
+fn is_region_symbol(c: char) -> bool { c.is_ascii_alphanumeric() }
+
+/// Parse the text format into a `GridFile`. Regions are emitted in the order of the `rules:` section.
+pub fn parse(text: &str) -> Result<GridFile, TextFormatError> {
+    let mut section = Section::None;
+    let mut board: Vec<(usize, &str)> = Vec::new();
+    let mut rules: Vec<(char, String)> = Vec::new();
+    let mut dominoes: Vec<Domino> = Vec::new();
+    let mut not_equal = NotEqualMode::default();
+    let mut max_pip = None;
+    let mut topology = Topology::Square;
+    for (i, raw) in text.lines().enumerate() {
+        let line_no = i + 1;
+        let trimmed = raw.trim();
+        if trimmed.starts_with('#') { continue; }
+        match trimmed.to_ascii_lowercase().as_str() {
+            "board:" => { section = Section::Board; continue; }
+            "rules:" => { section = Section::Rules; continue; }
+            "dominoes:" => { section = Section::Dominoes; continue; }
+            _ => {}
+        }
+        match section {
+            Section::None if trimmed.is_empty() => {}
+            Section::None if trimmed.starts_with("not-equal:") => {
+                let mode = format!("\"{}\"", trimmed["not-equal:".len()..].trim());
+                not_equal = serde_json::from_str(&mode)
+                    .or_else(|_| err(line_no, "not-equal must be 'all-different' or 'not-all-equal'"))?;
+            }
+            Section::None if trimmed.starts_with("topology:") => {
+                let value = trimmed["topology:".len()..].trim();
+                let torus = value.strip_prefix("torus").and_then(|size| size.trim().split_once('x'))
+                    .and_then(|(w, h)| Some(Topology::Torus { width: w.trim().parse().ok()?, height: h.trim().parse().ok()? }));
+                topology = match (value, torus) {
+                    ("square", _) => Topology::Square,
+                    ("hex", _) => Topology::Hex,
+                    (_, Some(torus)) => torus,
+                    _ => return err(line_no, format!("unsupported topology '{value}' (expected square, hex or torus WxH)")),
+                };
+            }
+            Section::None if trimmed.starts_with("max-pip:") => {
+                let value = trimmed["max-pip:".len()..].trim();
+                max_pip = Some(value.parse().or_else(|_| err(line_no, format!("invalid max-pip '{value}'")))?);
+            }
+            Section::None => return err(line_no, "expected a 'board:', 'rules:' or 'dominoes:' header"),
+            Section::Board => board.push((line_no, raw.trim_end())),
+            Section::Rules if trimmed.is_empty() => {}
+            Section::Rules => {
+                let mut parts = trimmed.splitn(2, char::is_whitespace);
+                let name = parts.next().unwrap_or_default();
+                let rule = parts.next().map(str::trim).unwrap_or_default();
+                let mut chars = name.chars();
+                let symbol = match (chars.next(), chars.next()) {
+                    (Some(c), None) if is_region_symbol(c) => c,
+                    _ => return err(line_no, format!("invalid region name '{name}'")),
+                };
+                if rule.is_empty() { return err(line_no, format!("region {symbol} has no rule")); }
+                if rules.iter().any(|(c, _)| *c == symbol) { return err(line_no, format!("region {symbol} listed twice")); }
+                rules.push((symbol, rule.to_string()));
+            }
+            Section::Dominoes => {
+                for token in trimmed.split(|c: char| c.is_whitespace() || c == ',').filter(|t| !t.is_empty()) {
+                    let parsed = token.split_once('-').and_then(|(a, b)| Some((a.parse().ok()?, b.parse().ok()?)));
+                    match parsed {
+                        Some(d) => dominoes.push(d),
+                        None => return err(line_no, format!("invalid domino '{token}', expected e.g. 3-5")),
+                    }
+                }
+            }
+        }
+    }
+    // Blank lines around the drawing are layout, not rows of holes.
+    while board.last().is_some_and(|(_, l)| l.trim().is_empty()) { board.pop(); }
+    while board.first().is_some_and(|(_, l)| l.trim().is_empty()) { board.remove(0); }
+    if board.is_empty() { return err(0, "missing 'board:' section"); }
+
+    let height = board.len() as u32;
+    let mut cells: HashMap<char, Vec<Coord>> = HashMap::new();
+    let mut free_cells: Vec<Coord> = Vec::new();
+    for (row, (line_no, line)) in board.iter().enumerate() {
+        let y = height - row as u32;
+        for (col, c) in line.chars().enumerate() {
+            if c == '.' || c == ' ' { continue; }
+            if c == '+' { free_cells.push((col as u32 + 1, y)); continue; }
+            if !is_region_symbol(c) { return err(*line_no, format!("unexpected board character '{c}'")); }
+            cells.entry(c).or_default().push((col as u32 + 1, y));
+        }
+    }
+    let mut grid = Vec::with_capacity(rules.len());
+    for (symbol, rule) in rules {
+        match cells.remove(&symbol) {
+            Some(mut coords) => {
+                coords.sort_unstable();
+                grid.push(GridEntry { rule, coords, ..Default::default() });
+            }
+            None => return err(0, format!("region {symbol} has a rule but no cells on the board")),
+        }
+    }
+    if let Some(symbol) = cells.keys().min() {
+        return err(0, format!("region {symbol} has no entry in the 'rules:' section"));
+    }
+    free_cells.sort_unstable();
+    Ok(GridFile { grid, dominoes, cells: free_cells, not_equal, max_pip, topology, ..Default::default() })
+}
+
+/// Region symbols handed out by `write`, in order.
+pub const SYMBOLS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// Render a `GridFile` in the text format. Fails if a cell belongs to several regions (the drawing holds one symbol per
+/// cell), if a coordinate is zero, or if there are more regions than available symbols.
+pub fn write(file: &GridFile) -> Result<String, TextFormatError> {
+    use std::fmt::Write;
+    // The drawing has no coordinates of its own, so lay it out in the internal convention.
+    let transform = file.transform();
+    let file = &file.normalised();
+    if file.grid.len() > SYMBOLS.len() {
+        return err(0, format!("{} regions exceed the {} available region symbols", file.grid.len(), SYMBOLS.len()));
+    }
+    if let Some(&c) = file.cells.iter().find(|c| c.0 == 0 || c.1 == 0) {
+        let (ux, uy) = transform.to_user(c);
+        return err(0, format!("coordinate ({ux},{uy}) is not 1-based"));
+    }
+    let mut owner: HashMap<Coord, char> = file.cells.iter().map(|&c| (c, '+')).collect();
+    for (entry, symbol) in file.grid.iter().zip(SYMBOLS.chars()) {
+        for &c in &entry.coords {
+            let (ux, uy) = transform.to_user(c);
+            if c.0 == 0 || c.1 == 0 { return err(0, format!("coordinate ({ux},{uy}) is not 1-based")); }
+            if let Some(prev) = owner.insert(c, symbol) && prev != symbol && prev != '+' {
+                return err(0, format!("cell ({ux},{uy}) belongs to more than one region"));
+            }
+        }
+    }
+    let mut out = String::new();
+    match &file.topology {
+        Topology::Square => {}
+        Topology::Hex => out.push_str("topology: hex\n"),
+        Topology::Torus { width, height } => { writeln!(out, "topology: torus {width}x{height}").ok(); }
+        Topology::Explicit { .. } => return err(0, "explicit adjacency lists cannot be written in the text format"),
+    }
+    if let Some(max_pip) = file.max_pip {
+        writeln!(out, "max-pip: {max_pip}").ok();
+    }
+    if !file.not_equal.is_default() {
+        writeln!(out, "not-equal: {}", serde_json::to_string(&file.not_equal).unwrap_or_default().trim_matches('"')).ok();
+    }
+    out.push_str("board:\n");
+    let max_x = owner.keys().map(|c| c.0).max().unwrap_or(0);
+    let max_y = owner.keys().map(|c| c.1).max().unwrap_or(0);
+    for y in (1..=max_y).rev() {
+        let row: String = (1..=max_x).map(|x| owner.get(&(x, y)).copied().unwrap_or('.')).collect();
+        // Keep one '.' on empty rows so they are not mistaken for layout blank lines.
+        let trimmed = row.trim_end_matches('.');
+        out.push_str(if trimmed.is_empty() { "." } else { trimmed });
+        out.push('\n');
+    }
+    out.push_str("rules:\n");
+    for (entry, symbol) in file.grid.iter().zip(SYMBOLS.chars()) {
+        writeln!(out, "{symbol} {}", canonical_rule(&entry.rule, file.not_equal)).ok();
+    }
+    out.push_str("dominoes:\n");
+    let list: Vec<String> = file.dominoes.iter().map(|(a, b)| format!("{a}-{b}")).collect();
+    out.push_str(&list.join(" "));
+    out.push('\n');
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::test_support::fixture;
+
+    fn sorted(file: &GridFile) -> Vec<(String, Vec<Coord>)> {
+        let mut v: Vec<(String, Vec<Coord>)> = file.grid.iter().map(|e| {
+            let mut c = e.coords.clone();
+            c.sort_unstable();
+            (e.rule.clone(), c)
+        }).collect();
+        v.sort();
+        v
+    }
+
+    #[test]
+    fn parse_easy_layout() {
+        let text = "# easy\nboard:\nABB\nCCC\nDD\nrules:\nA x\nB 1\nC =\nD 4\ndominoes:\n0-0 3-1, 3-3 4-0\n";
+        let parsed = parse(text).unwrap();
+        let easy = fixture("easy_grid.json");
+        assert_eq!(sorted(&parsed), sorted(&easy));
+        assert_eq!(parsed.dominoes, easy.dominoes);
+    }
+
+    #[test]
+    fn round_trip_fixtures() {
+        for name in ["easy_grid.json", "medium_grid.json", "hard_grid.json", "not_equal_grid.json"] {
+            let file = fixture(name);
+            let text = write(&file).unwrap();
+            let back = parse(&text).unwrap();
+            assert_eq!(sorted(&back), sorted(&file), "{name}");
+            assert_eq!(back.dominoes, file.dominoes, "{name}");
+        }
+    }
+
+    #[test]
+    fn combinator_rule_tokens_round_trip() {
+        let text = "board:\nAABB\nCCDD\nrules:\nA 3..7\nB {1, 3,5}\nC odd\nD distinct\ndominoes:\n1-2 3-5 1-1 2-4\n";
+        let parsed = parse(text).unwrap();
+        let rules: Vec<&str> = parsed.grid.iter().map(|e| e.rule.as_str()).collect();
+        assert_eq!(rules, ["3..7", "{1, 3,5}", "odd", "distinct"]);
+        let back = parse(&write(&parsed).unwrap()).unwrap();
+        assert_eq!(back.grid[1].rule, "{1,3,5}", "the writer uses canonical tokens");
+        assert_eq!(back.grid[0], parsed.grid[0]);
+    }
+
+    #[test]
+    fn legacy_not_equal_directive() {
+        let text = "not-equal: not-all-equal\nboard:\nAA\nrules:\nA !=\ndominoes:\n1-2\n";
+        let parsed = parse(text).unwrap();
+        assert_eq!(parsed.not_equal, NotEqualMode::NotAllEqual);
+        assert_eq!(write(&parsed).unwrap(), text);
+        assert_eq!(parse("not-equal: sometimes\nboard:\n").unwrap_err().line, 1);
+    }
+
+    #[test]
+    fn max_pip_directive() {
+        let text = "max-pip: 12\nboard:\nAA\nrules:\nA 21\ndominoes:\n9-12\n";
+        let parsed = parse(text).unwrap();
+        assert_eq!(parsed.max_pip(), 12);
+        assert_eq!(write(&parsed).unwrap(), text);
+        assert_eq!(parse("max-pip: lots\n").unwrap_err().line, 1);
+    }
+
+    #[test]
+    fn topology_directive() {
+        for name in ["hex_grid.json", "torus_grid.json"] {
+            let file = fixture(name);
+            let back = parse(&write(&file).unwrap()).unwrap();
+            assert_eq!(back.topology, file.topology, "{name}");
+            assert_eq!(sorted(&back), sorted(&file), "{name}");
+        }
+        assert!(parse("topology: torus 3\nboard:\n").is_err());
+        let explicit = GridFile { topology: Topology::Explicit { edges: vec![] }, ..fixture("easy_grid.json") };
+        assert!(write(&explicit).is_err());
+    }
+
+    #[test]
+    fn cells_outside_regions() {
+        let parsed = parse("board:\nA+\n+A\nrules:\nA =\ndominoes:\n1-1 2-3\n").unwrap();
+        assert_eq!(parsed.cells, vec![(1, 1), (2, 2)]);
+        assert_eq!(parsed.grid[0].coords, vec![(1, 2), (2, 1)]);
+        assert_eq!(write(&parsed).unwrap(), "board:\nA+\n+A\nrules:\nA =\ndominoes:\n1-1 2-3\n");
+    }
+
+    #[test]
+    fn holes_and_errors() {
+        let parsed = parse("board:\nA.A\nrules:\nA =\ndominoes:\n").unwrap();
+        assert_eq!(parsed.grid[0].coords, vec![(1, 1), (3, 1)]);
+        let raised = GridFile { grid: vec![GridEntry { rule: "x".into(), coords: vec![(2, 3), (2, 4)], ..Default::default() }], dominoes: vec![(1, 2)], ..Default::default() };
+        assert_eq!(parse(&write(&raised).unwrap()).unwrap().grid[0].coords, raised.grid[0].coords);
+        assert_eq!(parse("board:\nAB\nrules:\nA =\n").unwrap_err().message, "region B has no entry in the 'rules:' section");
+        assert_eq!(parse("board:\nA\nrules:\nA =\ndominoes:\n1/2\n").unwrap_err().line, 6);
+        assert!(parse("rules:\nA =\n").is_err());
+        let overlap = GridFile { grid: vec![
+            GridEntry { rule: "=".into(), coords: vec![(1, 1)], ..Default::default() },
+            GridEntry { rule: "x".into(), coords: vec![(1, 1)], ..Default::default() },
+        ], dominoes: vec![], ..Default::default() };
+        assert!(write(&overlap).is_err());
+    }
+
+    #[test]
+    fn write_uses_declared_coordinates() {
+        let mut flipped: GridFile = serde_json::from_str(r#"{"coordinates": {"origin": "top-left", "base": 0},
+            "grid": [{"rule": "=", "coords": [[0,0],[1,0]]}, {"rule": "x", "coords": [[0,1]]}], "dominoes": [[1,1]]}"#).unwrap();
+        assert!(write(&flipped).unwrap().starts_with("board:\nAA\nB\n"));
+        flipped.grid[1].coords = vec![(1, 0)];
+        assert_eq!(write(&flipped).unwrap_err().message, "cell (1,0) belongs to more than one region");
+    }
+}
@@ -0,0 +1,139 @@
+//! Cell adjacency of a puzzle board.
+//!
+//! Declared in a puzzle file as a `topology` object; the default is the classic square grid:
+//! - `{"kind": "square"}`: four-way orthogonal adjacency.
+//! - `{"kind": "hex"}`: hexagonal cells in rows, every odd row (counting the row at the declared origin as 1, the
+//!   bottom row by default) shifted right by half a cell, giving six neighbours.
+//! - `{"kind": "torus", "width": 4, "height": 3}`: square cells whose rows and columns wrap around.
+//! - `{"kind": "explicit", "edges": [[[1, 1], [2, 1]], ...]}`: exactly the listed cell pairs are adjacent.
+//!
+//! Explicit edges are written in the file's declared coordinate convention, like every other coordinate, and converted
+//! with it (`to_internal`). The torus size counts cells from the internal bottom-left `(1,1)`. Everything here works
+//! on internal coordinates; `GridFile::transform` keeps hex row parity tied to the declared origin.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::coords::CoordTransform;
+use crate::grid::Coord;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum Topology {
+    #[default]
+    Square,
+    Hex,
+    Torus { width: u32, height: u32 },
+    Explicit { edges: Vec<[Coord; 2]> },
+}
+
+impl Topology {
+    pub fn is_square(&self) -> bool { *self == Topology::Square }
+
+    /// Whether internal row `y` of a hex board is drawn half a cell to the right (odd rows; see `GridFile::transform`).
+    pub fn row_shifted(&self, y: u32) -> bool { matches!(self, Topology::Hex) && y % 2 == 1 }
+
+    /// Check the declaration against the board's cells (internal coordinates).
+    pub fn validate(&self, cells: &[Coord]) -> Result<(), String> {
+        match self {
+            Topology::Square | Topology::Hex => Ok(()),
+            Topology::Torus { width, height } => {
+                match cells.iter().find(|&&(x, y)| x == 0 || y == 0 || x > *width || y > *height) {
+                    Some(&(x, y)) => Err(format!("Cell ({x},{y}) lies outside the {width}x{height} torus.")),
+                    None => Ok(()),
+                }
+            }
+            Topology::Explicit { edges } => {
+                match edges.iter().flatten().find(|c| !cells.contains(c)) {
+                    Some(&(x, y)) => Err(format!("Adjacency list names cell ({x},{y}), which is not on the board.")),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+
+    /// Cells that would touch `c` on an unbounded board.
+    fn candidates(&self, (x, y): Coord) -> Vec<Coord> {
+        let (left, down) = (x.wrapping_sub(1), y.wrapping_sub(1));
+        match self {
+            Topology::Square | Topology::Explicit { .. } => vec![(left, y), (x + 1, y), (x, down), (x, y + 1)],
+            Topology::Hex => {
+                // The row above and below overlap this cell and the one on the side the row is shifted towards.
+                let side = if self.row_shifted(y) { x + 1 } else { left };
+                vec![(left, y), (x + 1, y), (x, down), (side, down), (x, y + 1), (side, y + 1)]
+            }
+            Topology::Torus { width, height } => {
+                let wrap = |v: u32, n: u32| if v == 0 { n } else if v > n { 1 } else { v };
+                vec![(wrap(left, *width), y), (wrap(x + 1, *width), y), (x, wrap(down, *height)), (x, wrap(y + 1, *height))]
+            }
+        }
+    }
+
+    /// Symmetric adjacency between the board's `cells`, each list sorted and free of duplicates and self-loops.
+    pub fn adjacency(&self, cells: &[Coord]) -> HashMap<Coord, Vec<Coord>> {
+        let mut adjacency: HashMap<Coord, Vec<Coord>> = cells.iter().map(|&c| (c, Vec::new())).collect();
+        let mut link = |a: Coord, b: Coord| {
+            if a == b || !adjacency.contains_key(&a) || !adjacency.contains_key(&b) { return; }
+            adjacency.get_mut(&a).unwrap().push(b);
+            adjacency.get_mut(&b).unwrap().push(a);
+        };
+        match self {
+            Topology::Explicit { edges } => edges.iter().for_each(|&[a, b]| link(a, b)),
+            _ => cells.iter().for_each(|&a| self.candidates(a).into_iter().for_each(|b| link(a, b))),
+        }
+        for list in adjacency.values_mut() {
+            list.sort_unstable();
+            list.dedup();
+        }
+        adjacency
+    }
+
+    /// Copy with explicit edges converted from the file's convention to the internal one.
+    pub fn to_internal(&self, t: CoordTransform) -> Topology {
+        match self {
+            Topology::Explicit { edges } => Topology::Explicit {
+                edges: edges.iter().map(|e| e.map(|c| t.to_internal(c))).collect(),
+            },
+            other => other.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(w: u32, h: u32) -> Vec<Coord> { (1..=w).flat_map(|x| (1..=h).map(move |y| (x, y))).collect() }
+
+    #[test]
+    fn square_and_hex_neighbours() {
+        let cells = rect(3, 3);
+        assert_eq!(Topology::Square.adjacency(&cells)[&(2, 2)], vec![(1, 2), (2, 1), (2, 3), (3, 2)]);
+        assert_eq!(Topology::Square.adjacency(&cells)[&(1, 1)], vec![(1, 2), (2, 1)]);
+        let hex = Topology::Hex.adjacency(&cells);
+        // Row 2 is unshifted, so it touches the cell to its lower left and upper left.
+        assert_eq!(hex[&(2, 2)], vec![(1, 1), (1, 2), (1, 3), (2, 1), (2, 3), (3, 2)]);
+        // Row 1 is shifted right, so it touches the cells up and up-right.
+        assert_eq!(hex[&(2, 1)], vec![(1, 1), (2, 2), (3, 1), (3, 2)]);
+        for (c, list) in &hex {
+            for n in list { assert!(hex[n].contains(c), "hex adjacency must be symmetric"); }
+        }
+    }
+
+    #[test]
+    fn torus_wraps_and_explicit_uses_edges() {
+        let torus = Topology::Torus { width: 3, height: 2 };
+        let adj = torus.adjacency(&rect(3, 2));
+        assert_eq!(adj[&(1, 1)], vec![(1, 2), (2, 1), (3, 1)]);
+        assert!(torus.validate(&[(4, 1)]).is_err());
+        let explicit = Topology::Explicit { edges: vec![[(1, 1), (3, 3)], [(1, 1), (3, 3)]] };
+        let adj = explicit.adjacency(&[(1, 1), (2, 1), (3, 3)]);
+        assert_eq!(adj[&(1, 1)], vec![(3, 3)]);
+        assert!(adj[&(2, 1)].is_empty());
+        assert!(explicit.validate(&[(1, 1)]).is_err());
+        let parsed: Topology = serde_json::from_str(r#"{"kind": "torus", "width": 4, "height": 2}"#).unwrap();
+        assert_eq!(parsed, Topology::Torus { width: 4, height: 2 });
+        assert!(serde_json::from_str::<Topology>(r#"{"kind": "triangle"}"#).is_err());
+    }
+}
@@ -0,0 +1,119 @@
+//! Search traces: every placement, pruning decision and backtrack of a solve, for debugging puzzles and heuristics.
+//!
+//! Install a sink with `GameGrid::set_trace`; both backends report through it. The CLI writes one JSON object per line
+//! (`--trace <file>`), starting each puzzle with a `start` event that holds the puzzle, so `replay` needs nothing else:
+//! ```json
+//! {"event":"start","puzzle":{"grid":[...],"dominoes":[...]}}
+//! {"event":"place","domino":0,"cells":[[1,1],[2,1]],"pips":[3,4]}
+//! {"event":"prune","region":1,"rule":"<5"}
+//! {"event":"backtrack","cells":[[1,1],[2,1]]}
+//! {"event":"solution"}
+//! ```
+//! Cells are in the puzzle's declared coordinate convention; `region` indexes the puzzle's `grid` array.
+
+use std::fmt;
+use std::io::BufRead;
+
+use serde::{Deserialize, Serialize};
+
+use crate::grid::{Coord, GameGrid, GridFile};
+
+/// Receives trace events as the search runs.
+pub type TraceSink = Box<dyn FnMut(&TraceEvent) + Send>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum TraceEvent {
+    /// A new puzzle; the board is empty. Written by the CLI, not by the search.
+    Start { puzzle: Box<GridFile> },
+    /// Inventory domino `domino` placed with `pips[i]` on `cells[i]`.
+    Place { domino: usize, cells: [Coord; 2], pips: [u8; 2] },
+    /// The last placement left region `region` unable to meet `rule` (or, on a full board, not meeting it).
+    Prune { region: usize, rule: String },
+    /// The domino on `cells` taken back.
+    Backtrack { cells: [Coord; 2] },
+    /// The board holds a solution.
+    Solution,
+}
+
+impl fmt::Display for TraceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pair = |[(ax, ay), (bx, by)]: [Coord; 2]| format!("({ax},{ay})-({bx},{by})");
+        match self {
+            TraceEvent::Start { .. } => write!(f, "start"),
+            TraceEvent::Place { domino, cells, pips } => write!(f, "place domino #{domino} as {}-{} on {}", pips[0], pips[1], pair(*cells)),
+            TraceEvent::Prune { region, rule } => write!(f, "prune: region {} ('{rule}') cannot be met", region + 1),
+            TraceEvent::Backtrack { cells } => write!(f, "backtrack from {}", pair(*cells)),
+            TraceEvent::Solution => write!(f, "solution"),
+        }
+    }
+}
+
+/// Read a JSON Lines trace, skipping blank lines. Errors name the 1-based line.
+pub fn read_trace(reader: impl BufRead) -> impl Iterator<Item = Result<TraceEvent, String>> {
+    reader.lines().enumerate()
+        .filter(|(_, line)| line.as_ref().map_or(true, |l| !l.trim().is_empty()))
+        .map(|(i, line)| {
+            let line = line.map_err(|e| e.to_string())?;
+            serde_json::from_str(&line).map_err(|e| format!("line {}: {e}", i + 1))
+        })
+}
+
+impl GameGrid {
+    /// Replay one traced step on this board (built from the trace's `start` puzzle). `Prune` and `Solution` change
+    /// nothing; `Start` is the caller's business.
+    pub fn apply_trace_event(&mut self, event: &TraceEvent) -> Result<(), String> {
+        match event {
+            TraceEvent::Place { domino, cells, pips } => {
+                let [a, b] = cells.map(|c| self.to_internal(c));
+                let [(ax, ay), (bx, by)] = *cells;
+                if !self.rule_index.contains_key(&a) || !self.rule_index.contains_key(&b)
+                    || self.occupied.contains_key(&a) || self.occupied.contains_key(&b) {
+                    return Err(format!("Cannot place on ({ax},{ay})-({bx},{by}): the trace does not match the puzzle."));
+                }
+                if *domino >= self.domino_inventory.len() || !self.domino_free(*domino) {
+                    return Err(format!("Domino #{domino} is not available: the trace does not match the puzzle."));
+                }
+                self.place(*domino, a, b, pips[0], pips[1]);
+            }
+            TraceEvent::Backtrack { cells } => {
+                let [a, b] = cells.map(|c| self.to_internal(c));
+                self.unplace(a, b);
+            }
+            TraceEvent::Start { .. } | TraceEvent::Prune { .. } | TraceEvent::Solution => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::{SOLVERS, SolveOptions};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn trace_records_and_replays_the_search() {
+        let file: GridFile = serde_json::from_str(
+            r#"{"grid": [{"rule": "<5", "coords": [[1,1],[2,1]]}, {"rule": "=", "coords": [[1,2],[2,2]]}], "dominoes": [[3,4],[1,2],[2,2]]}"#).unwrap();
+        for solver in SOLVERS {
+            let events = Arc::new(Mutex::new(Vec::new()));
+            let mut g = GameGrid::from_parsed(file.clone());
+            let sink = events.clone();
+            g.set_trace(Some(Box::new(move |e| sink.lock().unwrap().push(e.clone()))));
+            solver.solve(&mut g, &SolveOptions::default());
+            let events = events.lock().unwrap().clone();
+            assert!(events.iter().any(|e| matches!(e, TraceEvent::Prune { .. })), "{}: {events:?}", solver.name());
+            assert!(events.iter().any(|e| matches!(e, TraceEvent::Backtrack { .. })));
+            assert_eq!(events.last(), Some(&TraceEvent::Solution));
+
+            let mut replayed = GameGrid::from_parsed(file.clone());
+            for e in &events { replayed.apply_trace_event(e).unwrap(); }
+            assert_eq!(replayed.occupied, g.occupied, "{}", solver.name());
+        }
+        let line = serde_json::to_string(&TraceEvent::Prune { region: 1, rule: "<5".into() }).unwrap();
+        assert_eq!(line, r#"{"event":"prune","region":1,"rule":"<5"}"#);
+        let mut g = GameGrid::from_parsed(file);
+        assert!(g.apply_trace_event(&TraceEvent::Place { domino: 0, cells: [(5, 5), (6, 5)], pips: [3, 4] }).is_err());
+    }
+}
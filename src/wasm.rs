@@ -0,0 +1,76 @@
+//! JavaScript API for browser builds (`--features wasm`, target `wasm32-unknown-unknown`).
+//!
+//! Every function takes a puzzle document as a JSON string (a `GridFile`, optionally with a `solution` array as written
+//! by `--json`) and returns a string; failures are thrown as JavaScript `Error`s carrying the same messages as the CLI.
+//! ```js
+//! import init, { solve, countSolutions, render } from "./pips_solver.js";
+//! await init();
+//! const solved = JSON.parse(solve(JSON.stringify(puzzle)));
+//! ```
+
+use wasm_bindgen::prelude::*;
+
+use crate::grid::{GameGrid, NotEqualMode};
+use crate::json_format::{self, SolvedFile};
+use crate::solver::{self, SolveOptions, Solver};
+use crate::svg;
+
+fn error(message: impl AsRef<str>) -> JsError { JsError::new(message.as_ref()) }
+
+fn read(document: &str) -> Result<SolvedFile, JsError> { json_format::read_solved(document).map_err(error) }
+
+fn solver_named(name: Option<String>) -> Result<&'static dyn Solver, JsError> {
+    match name {
+        Some(name) => solver::by_name(&name).ok_or_else(|| error(format!("Unknown solver '{name}'."))),
+        None => Ok(solver::SOLVERS[0]),
+    }
+}
+
+/// Solve a puzzle and return the solved document, as `--json` prints it.
+#[wasm_bindgen]
+pub fn solve(puzzle: &str, solver: Option<String>) -> Result<String, JsError> {
+    let document = read(puzzle)?;
+    let mut g = GameGrid::from_parsed(document.puzzle.clone());
+    solver_named(solver)?.solve(&mut g, &SolveOptions::default()).0.ok_or_else(|| error("No solution found."))?;
+    Ok(json_format::write_solved(&document.puzzle, &g.placements()))
+}
+
+/// Count solutions, stopping at `limit`: `{"solutions": N, "complete": bool, "nodes": N}`.
+#[wasm_bindgen(js_name = countSolutions)]
+pub fn count_solutions(puzzle: &str, limit: Option<u32>, solver: Option<String>) -> Result<String, JsError> {
+    let mut g = GameGrid::from_parsed(read(puzzle)?.puzzle);
+    let limit = limit.map(|n| n as usize);
+    let opts = SolveOptions { max_solutions: limit, ..Default::default() };
+    let stats = solver_named(solver)?.enumerate(&mut g, &opts, &mut |_| false);
+    let complete = !stats.aborted && limit.is_none_or(|n| stats.solutions < n);
+    Ok(serde_json::json!({ "solutions": stats.solutions, "complete": complete, "nodes": stats.nodes }).to_string())
+}
+
+/// Check the document's solution: `{"valid": true}` or `{"valid": false, "error": "..."}`.
+#[wasm_bindgen]
+pub fn verify(document: &str) -> Result<String, JsError> {
+    let document = read(document)?;
+    let mut g = GameGrid::from_parsed(document.puzzle);
+    Ok(match g.verify_placements(&document.solution) {
+        Ok(()) => serde_json::json!({ "valid": true }),
+        Err(message) => serde_json::json!({ "valid": false, "error": message }),
+    }.to_string())
+}
+
+/// Draw the document's board and (possibly partial) solution as `ascii`, `svg` or canonical `json`.
+#[wasm_bindgen]
+pub fn render(document: &str, format: &str) -> Result<String, JsError> {
+    let document = read(document)?;
+    let mut g = GameGrid::from_parsed(document.puzzle.clone());
+    g.apply_placements(&document.solution).map_err(error)?;
+    match format {
+        "ascii" => Ok(g.ascii_board(false)),
+        "svg" => Ok(svg::render(&g)),
+        "json" => Ok(json_format::write_solved(&document.puzzle, &g.placements())),
+        other => Err(error(format!("Unknown format '{other}' (expected one of: ascii, svg, json)."))),
+    }
+}
+
+/// Canonical spelling of a rule token, e.g. `"sum: 5"` becomes `"5"`; unrecognised rules are returned unchanged.
+#[wasm_bindgen(js_name = canonicalRule)]
+pub fn canonical_rule(rule: &str) -> String { crate::grid::canonical_rule(rule, NotEqualMode::default()) }
@@ -0,0 +1,53 @@
+/* Exercises the C API; run by the `c_api_program` test in tests/cli_tests.rs with a puzzle path as argument. */
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "pips_solver.h"
+
+#define CHECK(cond) do { if (!(cond)) { fprintf(stderr, "%s:%d: check failed: %s (%s)\n", __FILE__, __LINE__, #cond, pips_last_error()); return 1; } } while (0)
+
+static char *read_file(const char *path) {
+    FILE *f = fopen(path, "rb");
+    if (!f) return NULL;
+    fseek(f, 0, SEEK_END);
+    long size = ftell(f);
+    fseek(f, 0, SEEK_SET);
+    char *text = malloc(size + 1);
+    size_t read = fread(text, 1, size, f);
+    text[read] = '\0';
+    fclose(f);
+    return text;
+}
+
+int main(int argc, char **argv) {
+    CHECK(argc == 2);
+    char *json = read_file(argv[1]);
+    CHECK(json != NULL);
+
+    PipsPuzzle *puzzle = NULL;
+    CHECK(pips_puzzle_from_json(json, &puzzle) == PIPS_OK);
+    free(json);
+
+    uint64_t count = 0;
+    CHECK(pips_count_solutions(puzzle, NULL, 0, &count) == PIPS_OK);
+    CHECK(count >= 1);
+    CHECK(pips_solve(puzzle, "dlx") == PIPS_OK);
+    char *result = pips_result_json(puzzle);
+    CHECK(result != NULL && strstr(result, "\"solution\"") != NULL);
+    pips_string_free(result);
+    CHECK(pips_solve(puzzle, "magic") == PIPS_USAGE);
+    CHECK(strstr(pips_last_error(), "magic") != NULL);
+    pips_puzzle_free(puzzle);
+
+    PipsPuzzle *unsolvable = NULL;
+    CHECK(pips_puzzle_from_json("{\"grid\": [{\"rule\": \"=\", \"coords\": [[1,1],[2,1]]}], \"dominoes\": [[1,2]]}", &unsolvable) == PIPS_OK);
+    CHECK(pips_solve(unsolvable, NULL) == PIPS_UNSOLVABLE);
+    pips_puzzle_free(unsolvable);
+
+    CHECK(pips_puzzle_from_json("{", &puzzle) == PIPS_ERROR);
+    CHECK(pips_puzzle_from_json(NULL, &puzzle) == PIPS_USAGE);
+
+    printf("%llu solution(s)\n", (unsigned long long)count);
+    return 0;
+}
@@ -92,7 +92,7 @@ fn ansi_strip(s: &str) -> String {
     let mut chars = s.chars().peekable();
     while let Some(c) = chars.next() {
         if c == '\u{1b}' { // skip until 'm'
-            while let Some(n) = chars.next() { if n == 'm' { break; } }
+            for n in chars.by_ref() { if n == 'm' { break; } }
         } else { out.push(c); }
     }
     out
@@ -107,3 +107,359 @@ fn run_not_equal_grid_solves() {
     // Should contain two numbers that differ (1 and 2 in some order)
     assert!(out.contains('1') && out.contains('2'));
 }
+
+#[test]
+fn run_batch_directory_jsonl() {
+    let dir = fixture("");
+    let (out, err, code) = cargo_run(&dir, &["batch", "--jobs", "2", "--jsonl"]);
+    assert_eq!(code, 2, "unsolvable fixture should fail the batch; stderr: {err}");
+    assert!(out.lines().count() >= 5);
+    assert!(out.contains("\"status\":\"unsolvable\"") && out.contains("\"status\":\"solved\""));
+}
+
+#[test]
+fn run_batch_every_solver_agrees() {
+    let dir = fixture("");
+    let statuses = |solver: &str| -> Vec<String> {
+        let (out, err, code) = cargo_run(&dir, &["batch", "--jsonl", "--solver", solver]);
+        assert_eq!(code, 2, "{solver}: {err}");
+        out.lines().map(|l| {
+            let v: serde_json::Value = serde_json::from_str(l).unwrap();
+            format!("{} {}", v["path"], v["status"])
+        }).collect()
+    };
+    assert_eq!(statuses("dlx"), statuses("backtrack"));
+    let (_, err, code) = cargo_run(&fixture("easy_grid.json"), &["--solver", "nope"]);
+    assert_eq!(code, 1);
+    assert!(err.contains("Unknown solver"), "{err}");
+}
+
+#[test]
+fn run_warns_about_unmarked_overlaps() {
+    let path = std::env::temp_dir().join(format!("pips_overlap_{}.json", std::process::id()));
+    std::fs::write(&path, r#"{"grid": [{"rule": "x", "coords": [[1,1],[2,1]]}, {"rule": "<9", "coords": [[2,1]]}], "dominoes": [[1,2]]}"#).unwrap();
+    let name = path.to_str().unwrap();
+    let (out, err, code) = cargo_run(name, &["--no-color"]);
+    assert_eq!(code, 0, "{err}");
+    assert!(!out.is_empty());
+    assert!(err.contains("Warning: Regions 1 ('x') and 2 ('<9') share cell (2,1)"), "{err}");
+    // Every command that loads a puzzle warns, not just solving.
+    for command in [&["convert"][..], &["cnf"], &["count"], &["batch"], &["dedupe"]] {
+        let (_, err, _) = cargo_run(name, command);
+        assert!(err.contains("Warning: Regions 1 ('x') and 2 ('<9')"), "{command:?}: {err}");
+    }
+    let (_, err, _) = cargo_run(name, &["batch"]);
+    assert!(err.contains(&format!("{name}: Warning:")), "{err}");
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn run_reads_puzzle_from_stdin() {
+    use std::io::Write;
+    use std::process::Stdio;
+    let mut child = Command::new(env!("CARGO"))
+        .args(["run", "--quiet", "--", "--no-color", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to run binary");
+    let easy = std::fs::read_to_string(fixture("easy_grid.json")).unwrap();
+    child.stdin.take().unwrap().write_all(easy.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let (file_out, _, _) = cargo_run(&fixture("easy_grid.json"), &["--no-color"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), file_out);
+}
+
+#[test]
+fn run_text_format_fixture() {
+    let (text_out, err, code) = cargo_run(&fixture("easy_grid.pips"), &["--no-color"]);
+    assert_eq!(code, 0, "stderr: {err}");
+    let (json_out, _, _) = cargo_run(&fixture("easy_grid.json"), &["--no-color"]);
+    assert_eq!(text_out, json_out);
+}
+
+/// `serve` child process on a free port, killed when dropped.
+struct Server { child: std::process::Child, port: u16 }
+
+impl Server {
+    fn start() -> Self {
+        use std::io::BufRead;
+        let mut child = Command::new(env!("CARGO_BIN_EXE_pips_solver"))
+            .args(["serve", "--port", "0", "--timeout-ms", "2000"])
+            .stdout(std::process::Stdio::piped())
+            .spawn().expect("failed to start server");
+        let mut line = String::new();
+        std::io::BufReader::new(child.stdout.take().unwrap()).read_line(&mut line).unwrap();
+        let port = line.trim().rsplit(':').next().and_then(|p| p.parse().ok()).unwrap_or_else(|| panic!("no port in {line:?}"));
+        Server { child, port }
+    }
+
+    /// Send one request and return the status code and body.
+    fn request(&self, method: &str, path: &str, body: &str) -> (u16, String) {
+        use std::io::{Read, Write};
+        let mut stream = std::net::TcpStream::connect(("127.0.0.1", self.port)).unwrap();
+        write!(stream, "{method} {path} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{body}", body.len()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let status = response.split_whitespace().nth(1).and_then(|s| s.parse().ok()).unwrap();
+        let body = response.split_once("\r\n\r\n").map(|(_, b)| b.to_string()).unwrap_or_default();
+        (status, body)
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) { let _ = self.child.kill(); let _ = self.child.wait(); }
+}
+
+#[test]
+fn serve_answers_every_endpoint() {
+    let server = Server::start();
+    let puzzle = std::fs::read_to_string(fixture("easy_grid.json")).unwrap();
+    let (status, solved) = server.request("POST", "/solve?solver=dlx", &puzzle);
+    assert_eq!(status, 200, "{solved}");
+    assert!(solved.contains("\"solution\""));
+    assert_eq!(server.request("POST", "/verify", &solved), (200, "{\"valid\":true}\n".to_string()));
+    let (status, hint) = server.request("POST", "/hint", &puzzle);
+    assert_eq!(status, 200);
+    assert!(hint.contains("\"hint\":{\"cells\""), "{hint}");
+    let (_, count) = server.request("POST", "/count?limit=5", &puzzle);
+    assert!(count.contains("\"solutions\":"), "{count}");
+    let (status, svg) = server.request("POST", "/render?format=svg", &solved);
+    assert_eq!(status, 200);
+    assert!(svg.starts_with("<svg"));
+    assert_eq!(server.request("GET", "/solve", "").0, 405);
+    assert_eq!(server.request("POST", "/missing", &puzzle).0, 404);
+    assert_eq!(server.request("POST", "/solve", "not json").0, 400);
+}
+
+#[test]
+fn serve_turns_away_connections_over_the_limit() {
+    use std::io::Read;
+    let server = Server::start();
+    let connect = || std::net::TcpStream::connect(("127.0.0.1", server.port)).unwrap();
+    // Connections are accepted in order, so these 32 (the server's limit) are all in progress before the next one.
+    let idle: Vec<_> = (0..32).map(|_| connect()).collect();
+    let mut response = String::new();
+    connect().read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 503"), "{response}");
+    drop(idle);
+    let mut status = 503;
+    for _ in 0..50 {
+        status = server.request("POST", "/solve", "not json").0;
+        if status != 503 { break; }
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    assert_eq!(status, 400, "slots are freed when connections close");
+}
+
+#[test]
+fn serve_times_out_long_searches() {
+    let server = Server::start();
+    // Counting every solution of a free 6x6 board would take practically forever.
+    let cells: Vec<String> = (1..=6).flat_map(|x| (1..=6).map(move |y| format!("[{x},{y}]"))).collect();
+    let dominoes: Vec<String> = (0..18).map(|i| format!("[{},{}]", i % 7, i / 7)).collect();
+    let puzzle = format!(r#"{{"grid": [{{"rule": "x", "coords": [{}]}}], "dominoes": [{}]}}"#, cells.join(","), dominoes.join(","));
+    let started = std::time::Instant::now();
+    let (status, body) = server.request("POST", "/count?timeout_ms=200", &puzzle);
+    assert_eq!(status, 200);
+    assert!(body.contains("\"complete\":false"), "{body}");
+    assert!(started.elapsed() < std::time::Duration::from_secs(2), "the request must not outlive its timeout");
+}
+
+#[test]
+fn wasm_feature_builds_for_the_browser() {
+    let sysroot = Command::new("rustc").args(["--print", "sysroot"]).output().expect("rustc on PATH");
+    let target = PathBuf::from(String::from_utf8_lossy(&sysroot.stdout).trim()).join("lib/rustlib/wasm32-unknown-unknown");
+    if !target.exists() {
+        eprintln!("skipping: wasm32-unknown-unknown target not installed (rustup target add wasm32-unknown-unknown)");
+        return;
+    }
+    let target_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("wasm");
+    let output = Command::new(env!("CARGO"))
+        .args(["build", "--quiet", "--lib", "--target", "wasm32-unknown-unknown", "--features", "wasm", "--target-dir"])
+        .arg(&target_dir)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output().expect("failed to run cargo");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(target_dir.join("wasm32-unknown-unknown/debug/pips_solver.wasm").exists());
+}
+
+#[test]
+fn c_api_program() {
+    let manifest = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let profile_dir = PathBuf::from(env!("CARGO_BIN_EXE_pips_solver")).parent().unwrap().to_path_buf();
+    let lib_name = if cfg!(target_os = "macos") { "libpips_solver.dylib" } else { "libpips_solver.so" };
+    let Some(lib_dir) = [profile_dir.join("deps"), profile_dir.clone()].into_iter().find(|d| d.join(lib_name).exists()) else {
+        eprintln!("skipping: {lib_name} not built");
+        return;
+    };
+    let exe = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("ffi_test");
+    let compiled = match Command::new("cc")
+        .arg(manifest.join("tests/c/ffi_test.c"))
+        .arg("-I").arg(manifest.join("include"))
+        .arg("-L").arg(&lib_dir)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .args(["-lpips_solver", "-Wall", "-Werror", "-o"]).arg(&exe)
+        .output() {
+        Ok(output) => output,
+        Err(_) => { eprintln!("skipping: no C compiler (cc) on PATH"); return; }
+    };
+    assert!(compiled.status.success(), "{}", String::from_utf8_lossy(&compiled.stderr));
+    let run = Command::new(&exe).arg(fixture("easy_grid.json")).output().unwrap();
+    assert!(run.status.success(), "{}", String::from_utf8_lossy(&run.stderr));
+    assert!(String::from_utf8_lossy(&run.stdout).contains("solution(s)"));
+}
+
+#[test]
+fn python_bindings() {
+    let has_pytest = Command::new("python3").args(["-c", "import pytest"]).output().is_ok_and(|o| o.status.success());
+    if !has_pytest {
+        eprintln!("skipping: python3 with pytest not available");
+        return;
+    }
+    let manifest = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let target_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("python");
+    let built = Command::new(env!("CARGO"))
+        .args(["build", "--quiet", "--lib", "--features", "python", "--target-dir"]).arg(&target_dir)
+        .current_dir(&manifest)
+        .output().expect("failed to run cargo");
+    assert!(built.status.success(), "{}", String::from_utf8_lossy(&built.stderr));
+    // Python imports the extension by module name.
+    let module_dir = target_dir.join("module");
+    std::fs::create_dir_all(&module_dir).unwrap();
+    let lib_name = if cfg!(target_os = "macos") { "libpips_solver.dylib" } else { "libpips_solver.so" };
+    std::fs::copy(target_dir.join("debug").join(lib_name), module_dir.join("pips_solver.so")).unwrap();
+    let run = Command::new("python3")
+        .args(["-m", "pytest", "-q", "-p", "no:cacheprovider"]).arg(manifest.join("tests/python"))
+        .env("PYTHONPATH", &module_dir)
+        .output().unwrap();
+    assert!(run.status.success(), "{}{}", String::from_utf8_lossy(&run.stdout), String::from_utf8_lossy(&run.stderr));
+}
+
+#[test]
+fn edit_session_saves_a_solvable_puzzle() {
+    use std::io::Write;
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("edited.json");
+    let _ = std::fs::remove_file(&path);
+    let mut child = Command::new(env!("CARGO_BIN_EXE_pips_solver"))
+        .arg("edit").arg(&path)
+        .stdin(std::process::Stdio::piped()).stdout(std::process::Stdio::piped())
+        .spawn().unwrap();
+    child.stdin.take().unwrap()
+        .write_all(b"paint new 1,1..2,1\nrule A sum 6\npaint new 1,2..2,2\nrule B =\ndomino 2-4 1-1\nsolve\nsave\nquit\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success());
+    assert!(stdout.contains("4 cells, 2 dominoes: 2 solutions"), "{stdout}");
+    let (out, err, code) = cargo_run(&path.to_string_lossy(), &["--no-color"]);
+    assert_eq!(code, 0, "{err}");
+    assert_eq!(normalize_ws(&out), normalize_ws(stdout.split("edit> ").nth(6).unwrap()));
+}
+
+#[test]
+fn trace_then_replay_ends_on_the_solution() {
+    let trace = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("medium.trace.jsonl");
+    let trace = trace.to_string_lossy().into_owned();
+    let (solved, err, code) = cargo_run(&fixture("medium_grid.json"), &["--no-color", "--solver", "dlx", "--trace", &trace]);
+    assert_eq!(code, 0, "{err}");
+    let lines = std::fs::read_to_string(&trace).unwrap();
+    assert!(lines.starts_with("{\"event\":\"start\""));
+    assert!(lines.contains("\"event\":\"prune\"") && lines.contains("\"event\":\"backtrack\""));
+
+    let (replayed, err, code) = cargo_run(&trace, &["replay", "--no-color"]);
+    assert_eq!(code, 0, "{err}");
+    let last = replayed.rsplit_once("solution\n").map(|(_, board)| board).unwrap();
+    assert_eq!(last, solved);
+    // --step waits for Enter after each step; "q" stops after the third.
+    use std::io::Write;
+    let mut child = Command::new(env!("CARGO_BIN_EXE_pips_solver"))
+        .args(["replay", "--no-color", "--step", &trace])
+        .stdin(std::process::Stdio::piped()).stdout(std::process::Stdio::piped())
+        .spawn().unwrap();
+    child.stdin.take().unwrap().write_all(b"\n\nq\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+    let (stepped, code) = (String::from_utf8_lossy(&output.stdout), output.status.code());
+    assert_eq!(code, Some(0));
+    assert!(stepped.contains("#3 ") && !stepped.contains("#4 "), "{stepped}");
+}
+
+#[test]
+fn animate_writes_an_svg_and_terminal_frames() {
+    let (svg, err, code) = cargo_run(&fixture("medium_grid.json"), &["--svg", "--animate", "solution", "--delay", "100"]);
+    assert_eq!(code, 0, "{err}");
+    assert!(svg.starts_with("<svg") && svg.matches("<svg").count() == 1);
+    assert!(svg.matches("<g visibility=\"hidden\">").count() > 2);
+    let (frames, err, code) = cargo_run(&fixture("unsolvable_grid.json"), &["--animate", "search", "--delay", "0"]);
+    assert_eq!(code, 2, "{err}");
+    assert!(frames.is_empty());
+}
+
+#[test]
+fn progress_reports_on_stderr() {
+    let (out, err, code) = cargo_run(&fixture("unsolvable_grid.json"), &["--progress"]);
+    assert_eq!(code, 2, "{err}");
+    assert!(out.is_empty());
+    assert!(err.contains("done: ") && err.contains("nodes, depth 0, ~100.0% of the search tree"), "{err}");
+    let (out, err, code) = cargo_run(&fixture("easy_grid.json"), &["--no-color", "--progress"]);
+    assert_eq!(code, 0, "{err}");
+    assert!(!out.contains("nodes") && err.lines().last().unwrap().starts_with("done: "), "{err}");
+}
+
+#[cfg(unix)]
+#[test]
+fn count_checkpoints_on_ctrl_c_and_resumes() {
+    // Far too many solutions to count here.
+    let puzzle = fixture("slow/open_board.json");
+    let checkpoint = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("open_board.checkpoint.json");
+    let _ = std::fs::remove_file(&checkpoint);
+    let interrupt = |args: &[&str]| -> serde_json::Value {
+        let child = Command::new(env!("CARGO_BIN_EXE_pips_solver")).args(args)
+            .stdout(std::process::Stdio::piped()).stderr(std::process::Stdio::piped()).spawn().unwrap();
+        // The first periodic checkpoint shows the handler is installed and the count under way.
+        let before = std::fs::read_to_string(&checkpoint).ok();
+        for _ in 0..200 {
+            if std::fs::read_to_string(&checkpoint).ok() != before { break; }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+        Command::new("kill").args(["-INT", &child.id().to_string()]).status().unwrap();
+        let output = child.wait_with_output().unwrap();
+        let err = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(output.status.code(), Some(1), "{err}");
+        assert!(err.contains("Interrupted after") && err.contains("count --resume"), "{err}");
+        serde_json::from_str(&std::fs::read_to_string(&checkpoint).unwrap()).unwrap()
+    };
+    let file = checkpoint.to_string_lossy().into_owned();
+    let first = interrupt(&["count", "--solver", "dlx", "--every", "0", "--checkpoint", &file, &puzzle]);
+    assert_eq!(first["solver"], "dlx");
+    assert!(!first["path"].as_array().unwrap().is_empty());
+    let second = interrupt(&["count", "--every", "0", "--resume", &file]);
+    assert!(second["solutions"].as_u64() > first["solutions"].as_u64(), "{second}");
+    std::fs::remove_file(&checkpoint).unwrap();
+}
+
+#[test]
+fn dedupe_groups_rewritten_copies() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("dedupe");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    // An L-shaped board, so that rotating it gives a different drawing.
+    let original = r#"{"grid": [{"rule": "=", "coords": [[1,1],[2,1]]}, {"rule": "5", "coords": [[1,2],[1,3]]}], "dominoes": [[2,2],[1,4]]}"#;
+    // Regions, coordinates and dominoes reordered, and the board moved by (2,3).
+    let moved = r#"{"grid": [{"rule": "5", "coords": [[3,6],[3,5]]}, {"rule": "=", "coords": [[4,4],[3,4]]}], "dominoes": [[4,1],[2,2]]}"#;
+    // The original turned a quarter turn anticlockwise.
+    let rotated = r#"{"grid": [{"rule": "=", "coords": [[3,1],[3,2]]}, {"rule": "5", "coords": [[2,1],[1,1]]}], "dominoes": [[2,2],[1,4]]}"#;
+    std::fs::write(dir.join("a.json"), original).unwrap();
+    std::fs::write(dir.join("b.json"), moved).unwrap();
+    std::fs::write(dir.join("c.json"), original.replace("\"5\"", "\"6\"")).unwrap();
+    std::fs::write(dir.join("d.json"), rotated).unwrap();
+    let (out, err, code) = cargo_run("", &["dedupe", &dir.to_string_lossy()]);
+    assert_eq!(code, 0, "{err}");
+    assert!(out.contains("a.json") && out.contains("b.json") && !out.contains("c.json") && !out.contains("d.json"), "{out}");
+    assert!(out.ends_with("4 puzzles, 3 distinct, 1 duplicate\n"), "{out}");
+
+    let (out, err, code) = cargo_run("", &["dedupe", "--symmetric", &dir.to_string_lossy()]);
+    assert_eq!(code, 0, "{err}");
+    assert!(out.contains("a.json") && out.contains("b.json") && out.contains("d.json") && !out.contains("c.json"), "{out}");
+    assert!(out.ends_with("4 puzzles, 2 distinct, 2 duplicates\n"), "{out}");
+}
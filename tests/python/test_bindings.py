@@ -0,0 +1,55 @@
+"""pytest cases for the `python` feature; run by `python_bindings` in tests/cli_tests.rs with the built module on PYTHONPATH."""
+import json
+import pathlib
+
+import pytest
+
+from pips_solver import Puzzle
+
+GRIDS = pathlib.Path(__file__).resolve().parent.parent / "grids"
+
+
+def test_solve_fixture_from_path():
+    puzzle = Puzzle.from_path(str(GRIDS / "easy_grid.json"))
+    solution = puzzle.solve()
+    assert solution is not None
+    covered = {cell for placement in solution for cell in placement["cells"]}
+    assert len(covered) == 2 * len(solution)
+    assert puzzle.solve(solver="dlx") is not None
+
+
+def test_text_format_and_dict_agree():
+    text = Puzzle.from_path(str(GRIDS / "easy_grid.pips"))
+    data = json.loads((GRIDS / "easy_grid.json").read_text())
+    assert Puzzle.from_dict(data).count() == text.count()
+
+
+def test_enumerate_and_count():
+    puzzle = Puzzle.from_dict({"grid": [{"rule": "x", "coords": [[1, 1], [2, 1], [1, 2], [2, 2]]}], "dominoes": [[1, 2], [3, 4]]})
+    assert puzzle.count() == 16
+    assert len(puzzle.solutions(limit=3)) == 3
+    assert puzzle.count(limit=5, solver="dlx") == 5
+
+
+def test_rating_and_unsolvable():
+    assert Puzzle.from_path(str(GRIDS / "hard_grid.json")).rate()["label"] == "hard"
+    unsolvable = Puzzle.from_path(str(GRIDS / "unsolvable_grid.json"))
+    assert unsolvable.solve() is None
+    assert unsolvable.rate() is None
+
+
+def test_svg_with_and_without_solution():
+    puzzle = Puzzle.from_json('{"grid": [{"rule": "=", "coords": [[1, 1], [2, 1]]}], "dominoes": [[2, 2]]}')
+    assert ">2</text>" in puzzle.svg()
+    assert ">2</text>" not in puzzle.svg([])
+    with pytest.raises(ValueError):
+        puzzle.svg([{"cells": [[1, 1], [3, 1]], "pips": [2, 2]}])
+
+
+def test_errors_raise_value_error():
+    with pytest.raises(ValueError):
+        Puzzle.from_json("{")
+    with pytest.raises(ValueError):
+        Puzzle.from_dict({"grid": [], "dominoes": [[7, 7]]})
+    with pytest.raises(ValueError, match="Unknown solver"):
+        Puzzle.from_dict({"grid": [], "dominoes": []}).solve(solver="magic")